@@ -0,0 +1,42 @@
+/* Generated from src/ffi.rs, regenerate with UPDATE_HEADER=1 cargo test. */
+
+#ifndef ADVENT_2022_H
+#define ADVENT_2022_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum AdventCode {
+    ADVENT_OK = 0,
+    ADVENT_UNKNOWN_DAY = 1,
+    ADVENT_UNKNOWN_PART = 2,
+    ADVENT_NULL_INPUT = 3,
+    ADVENT_INVALID_UTF8 = 4,
+    ADVENT_INVALID_INPUT = 5,
+    ADVENT_PANICKED = 6,
+    ADVENT_OVERFLOW = 7,
+} AdventCode;
+
+/* The answer when code is ADVENT_OK, the error message otherwise.
+ * text is owned by the caller, release it with advent_string_free. */
+typedef struct AdventAnswer {
+    AdventCode code;
+    char *text;
+} AdventAnswer;
+
+/* Solves part (1 or 2) of day for len bytes of UTF-8 input, or both parts
+ * joined when part is 0. input may be NULL only if len is 0. */
+AdventAnswer advent_solve(uint32_t day, uint32_t part, const uint8_t *input, size_t len);
+
+/* Releases the text of an AdventAnswer, NULL is ignored. */
+void advent_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ADVENT_2022_H */
@@ -0,0 +1,24 @@
+use std::{fs, io, path::Path};
+
+use crate::input::normalize;
+
+/// Directory of the accepted answers to our own inputs, one `day_N` file per day.
+pub const ANSWERS: &str = "./answers";
+
+/// The accepted answer of a day, if one was recorded.
+pub fn expected(dir: &Path, day: usize) -> Option<String> {
+    fs::read_to_string(dir.join(format!("day_{}", day)))
+        .ok()
+        .map(|answer| normalize(&answer))
+}
+
+/// Records the answer of a day once the site accepted it.
+pub fn record(dir: &Path, day: usize, answer: &str) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(format!("day_{}", day)), normalize(answer) + "\n")
+}
+
+/// Whether an answer matches the recorded one, `None` if there is none to compare with.
+pub fn check(dir: &Path, day: usize, answer: &str) -> Option<bool> {
+    expected(dir, day).map(|expected| expected == normalize(answer))
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::params::{Param, Params};
+
+/// Directory of the snapshots of interrupted searches.
+pub const CHECKPOINTS: &str = "./checkpoints";
+
+/// Seconds between two snapshots of a long search, 0 disables them.
+pub const PARAM: Param = Param::new("checkpoint", 0).at_least(0);
+
+/// A state that can be written into a snapshot as a list of numbers and read back.
+pub trait Snapshot: Sized {
+    fn save(&self, fields: &mut Vec<i64>);
+
+    fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self>;
+}
+
+/// FNV-1a, unlike the std hashers it is guaranteed to stay the same between builds.
+pub fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The snapshots of the searches on one input.
+#[derive(Debug, Clone)]
+pub struct Checkpoints {
+    dir: PathBuf,
+    input: u64,
+    interval: Duration,
+}
+
+impl Checkpoints {
+    pub fn new(dir: impl Into<PathBuf>, input: &str, interval: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            input: hash(input),
+            interval,
+        }
+    }
+
+    /// Snapshots under [`CHECKPOINTS`] if the day's [`PARAM`] enables them.
+    pub fn from_params(input: &str, params: &Params) -> Option<Self> {
+        let secs = params.get(PARAM.name);
+        (secs > 0).then(|| Self::new(CHECKPOINTS, input, Duration::from_secs(secs as u64)))
+    }
+
+    /// The snapshot of one search, `name` should tell apart every search of the day that depends
+    /// on anything else than the input.
+    pub fn get(&self, name: &str) -> Checkpoint {
+        Checkpoint {
+            path: self.dir.join(name),
+            input: self.input,
+            interval: self.interval,
+            last: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    input: u64,
+    interval: Duration,
+    last: Instant,
+}
+
+impl Checkpoint {
+    /// Whether the last snapshot is old enough to be replaced.
+    pub fn due(&self) -> bool {
+        self.last.elapsed() >= self.interval
+    }
+
+    fn header(&self) -> String {
+        format!("input {:016x}\n", self.input)
+    }
+
+    /// The snapshot left by an earlier run on the same input.
+    pub fn load(&self) -> Option<String> {
+        let text = fs::read_to_string(&self.path).ok()?;
+        text.strip_prefix(&self.header()).map(str::to_owned)
+    }
+
+    /// Replaces the snapshot, an interruption while writing leaves the previous one intact.
+    pub fn save(&mut self, body: &str) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = self.path.with_extension("partial");
+        fs::write(&partial, self.header() + body)?;
+        fs::rename(&partial, &self.path)?;
+        self.last = Instant::now();
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumed_on_same_input() {
+        let dir = std::env::temp_dir().join(format!("advent-checkpoint-{}", std::process::id()));
+        let mut checkpoint = Checkpoints::new(&dir, "input", Duration::ZERO).get("day_0");
+        assert!(checkpoint.due());
+        assert_eq!(checkpoint.load(), None);
+
+        checkpoint.save("1 2 3\n").unwrap();
+        let resumed = Checkpoints::new(&dir, "input", Duration::ZERO).get("day_0");
+        assert_eq!(resumed.load().as_deref(), Some("1 2 3\n"));
+        let other = Checkpoints::new(&dir, "other input", Duration::ZERO).get("day_0");
+        assert_eq!(other.load(), None);
+
+        resumed.clear();
+        assert_eq!(checkpoint.load(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ureq::{Agent, AgentBuilder};
+
+const REPOSITORY: &str = "github.com/ivfranco/advent-of-code-2022";
+
+/// Optional file holding contact details, an email or a profile URL, sent along with every request
+/// so the site owner can reach out instead of blocking.
+pub const CONTACT_FILE: &str = "./CONTACT";
+
+/// Where the time of the last request is kept, to throttle across separate runs.
+pub const STAMP_FILE: &str = "./inputs/.last_request";
+
+const RETRIES: u32 = 3;
+const BACKOFF: Duration = Duration::from_secs(2);
+/// Minimum time between two requests to the site.
+pub const INTERVAL: Duration = Duration::from_secs(5);
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP client for the puzzle site that identifies itself, retries server errors and keeps a
+/// minimum interval between requests.
+pub struct Client {
+    agent: Agent,
+    base_url: String,
+    session: String,
+    retries: u32,
+    backoff: Duration,
+    throttle: Throttle,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str, contact: Option<&str>) -> Self {
+        Self {
+            agent: AgentBuilder::new()
+                .user_agent(&user_agent(contact))
+                .timeout(TIMEOUT)
+                .build(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            session: session.trim().to_owned(),
+            retries: RETRIES,
+            backoff: BACKOFF,
+            throttle: Throttle {
+                interval: INTERVAL,
+                stamp: None,
+                last: None,
+            },
+        }
+    }
+
+    /// Retries a request failed with a 5xx status up to `retries` times, waiting `backoff` before
+    /// the first retry and twice as long before each one after.
+    pub fn with_retries(self, retries: u32, backoff: Duration) -> Self {
+        Self {
+            retries,
+            backoff,
+            ..self
+        }
+    }
+
+    /// Keeps at least `interval` between requests, also across processes when a `stamp` file is
+    /// given.
+    pub fn with_throttle(self, interval: Duration, stamp: Option<PathBuf>) -> Self {
+        Self {
+            throttle: Throttle {
+                interval,
+                stamp,
+                last: None,
+            },
+            ..self
+        }
+    }
+
+    /// Fetches `path` relative to the base URL with the session cookie set.
+    pub fn get(&mut self, path: &str) -> Result<String, Box<dyn Error>> {
+        let url = format!("{}{}", self.base_url, path);
+        let cookie = format!("session={}", self.session);
+        let mut backoff = self.backoff;
+
+        for attempt in 0.. {
+            self.throttle.wait();
+            match self.agent.get(&url).set("Cookie", &cookie).call() {
+                Ok(response) => return Ok(response.into_string()?),
+                Err(ureq::Error::Status(status, _)) if status >= 500 && attempt < self.retries => {
+                    eprintln!(
+                        "{} answered {}, retrying in {:?}",
+                        self.base_url, status, backoff
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        unreachable!("returned on the last attempt")
+    }
+}
+
+fn user_agent(contact: Option<&str>) -> String {
+    match contact.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(contact) => format!("{} by {}", REPOSITORY, contact),
+        None => REPOSITORY.to_owned(),
+    }
+}
+
+/// Reads the contact details from [`CONTACT_FILE`], if there are any.
+pub fn contact() -> Option<String> {
+    fs::read_to_string(CONTACT_FILE).ok()
+}
+
+struct Throttle {
+    interval: Duration,
+    stamp: Option<PathBuf>,
+    last: Option<SystemTime>,
+}
+
+impl Throttle {
+    fn last(&self) -> Option<SystemTime> {
+        let stamped = self
+            .stamp
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|millis| millis.trim().parse::<u64>().ok())
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis));
+
+        self.last.max(stamped)
+    }
+
+    fn wait(&mut self) {
+        if let Some(last) = self.last() {
+            // a stamp from the future means a skewed clock, wait the full interval in that case
+            let elapsed = SystemTime::now()
+                .duration_since(last)
+                .unwrap_or(Duration::ZERO);
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+
+        let now = SystemTime::now();
+        self.last = Some(now);
+        if let Some(path) = &self.stamp {
+            let millis = now
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            // throttling within this process still works without the stamp
+            let _ = fs::write(path, millis.to_string());
+        }
+    }
+}
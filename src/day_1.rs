@@ -1,19 +1,75 @@
-pub fn solution(input: &str) -> String {
-    let elves = parse(input);
-    format!("{}, {}", part_one(&elves), part_two(&elves))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let elves = parse(input)?;
+    Ok(format!("{}, {}", part_one(&elves), part_two(&elves)))
+}
+
+pub fn solution_sorting(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let elves = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&elves),
+        part_two_sorting(&elves)
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let elves = parse(input)?;
+    let printed = print(&elves);
+    printed_back(&elves, parse(&printed))?;
+    Ok(printed)
 }
 
-fn parse(input: &str) -> Vec<Vec<u32>> {
+/// About `size` elves carrying a few snacks each.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(3))
+        .map(|_| {
+            (0..rng.range(1, 6))
+                .map(|_| rng.range(1000, 60000).to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn parse(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
     input
         .split("\n\n")
         .map(|elf| {
             elf.lines()
-                .map(|l| l.parse::<u32>().expect("calories: unsigned integer"))
+                .map(|l| {
+                    l.parse::<u32>()
+                        .map_err(|_| ParseError::new(format!("calories: {:?}", l)))
+                })
                 .collect()
         })
         .collect()
 }
 
+fn print(elves: &[Vec<u32>]) -> String {
+    elves
+        .iter()
+        .map(|elf| {
+            elf.iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 fn part_one(elves: &[Vec<u32>]) -> u32 {
     elves
         .iter()
@@ -32,6 +88,12 @@ fn part_two(elves: &[Vec<u32>]) -> u32 {
         .sum()
 }
 
+fn part_two_sorting(elves: &[Vec<u32>]) -> u32 {
+    let mut calories: Vec<u32> = elves.iter().map(|elf| elf.iter().sum()).collect();
+    calories.sort_unstable_by(|a, b| b.cmp(a));
+    calories.iter().take(3).sum()
+}
+
 struct Top<const N: usize>([u32; N]);
 
 impl<const N: usize> Top<N> {
@@ -52,3 +114,36 @@ impl<const N: usize> Top<N> {
         self.0.iter().sum()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000";
+
+    #[test]
+    fn example_part_one() {
+        let elves = parse(INPUT).unwrap();
+        assert_eq!(part_one(&elves), 24000);
+    }
+
+    #[test]
+    fn example_part_two() {
+        let elves = parse(INPUT).unwrap();
+        assert_eq!(part_two(&elves), 45000);
+        assert_eq!(part_two_sorting(&elves), 45000);
+    }
+}
@@ -1,16 +1,41 @@
 use nom::{
-    bytes::complete::{tag, take_while},
-    character::complete::{line_ending, space1},
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{i64, line_ending, space1},
+    combinator::all_consuming,
     multi::separated_list1,
+    sequence::preceded,
     IResult, Parser,
 };
 
-pub fn solution(input: &str) -> String {
-    let insts = parse(input);
-    format!("{}\n{}", part_one(&insts), part_two(&insts))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let insts = parse(input)?;
+    Ok(format!("{}\n{}", part_one(&insts), part_two(&insts)))
+}
+
+/// The CRT image of part two.
+pub fn render_crt(input: &str, _params: &Params) -> Result<String, SolveError> {
+    Ok(part_two(&parse(input)?))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let insts = parse(input)?;
+    let printed = print(&insts);
+    printed_back(&insts, parse(&printed))?;
+    Ok(printed)
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 enum Inst {
     Noop,
     Addx(i64),
@@ -25,12 +50,29 @@ impl Inst {
     }
 }
 
-fn parse(input: &str) -> Vec<Inst> {
-    let (remain, insts) = p_insts(input).expect("invalid input");
-    if !remain.is_empty() {
-        panic!("incomplete parse");
+/// Cycles the CRT of part two draws for, the program must run at least as long.
+const CYCLES: i64 = 240;
+
+fn parse(input: &str) -> Result<Vec<Inst>, ParseError> {
+    let (_, insts) = all_consuming(p_insts)(input)?;
+    if insts.iter().map(|inst| inst.cycle()).sum::<i64>() < CYCLES {
+        return Err(ParseError::new(format!(
+            "program ends before cycle {}",
+            CYCLES
+        )));
     }
+    Ok(insts)
+}
+
+fn print(insts: &[Inst]) -> String {
     insts
+        .iter()
+        .map(|inst| match inst {
+            Inst::Noop => "noop".to_owned(),
+            Inst::Addx(dx) => format!("addx {}", dx),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn p_insts(input: &str) -> IResult<&str, Vec<Inst>> {
@@ -38,17 +80,10 @@ fn p_insts(input: &str) -> IResult<&str, Vec<Inst>> {
 }
 
 fn p_inst(input: &str) -> IResult<&str, Inst> {
-    let (input, op) = tag("noop").or(tag("addx")).parse(input)?;
-    match op {
-        "noop" => Ok((input, Inst::Noop)),
-        "addx" => {
-            let (input, _) = space1(input)?;
-            let (input, imm) = take_while(|c: char| c != '\n')(input)?;
-            let inst = Inst::Addx(imm.parse().expect("invalid immediate"));
-            Ok((input, inst))
-        }
-        _ => unreachable!("invalid op"),
-    }
+    alt((
+        tag("noop").map(|_| Inst::Noop),
+        preceded(tag("addx").and(space1), i64).map(Inst::Addx),
+    ))(input)
 }
 
 struct Circuit {
@@ -279,7 +314,7 @@ noop";
 
     #[test]
     fn example_part_one() {
-        let insts = parse(INPUT);
+        let insts = parse(INPUT).unwrap();
         assert_eq!(part_one(&insts), 13140);
     }
 
@@ -293,7 +328,7 @@ noop";
 #######.......#######.......#######.....
 ";
 
-        let insts = parse(INPUT);
+        let insts = parse(INPUT).unwrap();
         assert_eq!(part_two(&insts), CRT);
     }
 }
@@ -1,56 +1,92 @@
+use std::fmt::{self, Display};
+
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
     character::complete::{digit1, newline, one_of, space1},
+    combinator::{all_consuming, map_res},
     multi::separated_list1,
     sequence::{delimited, preceded, separated_pair},
     IResult, Parser,
 };
 
-pub fn solution(input: &str) -> String {
-    let monkeys = parse(input);
-    format!("{}, {}", part_one(&monkeys), part_two(&monkeys))
+use crate::{
+    input::{printed_back, ParseError},
+    overflow::{Arith, Overflow},
+    params::{Param, Params},
+    registry::SolveError,
+};
+
+pub const PARAMS: &[Param] = &[
+    Param::new("rounds", 20)
+        .at_least(0)
+        .at_most(u32::MAX as i64),
+    Param::new("rounds_two", 10000)
+        .at_least(0)
+        .at_most(u32::MAX as i64),
+];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let monkeys = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&monkeys, params.get("rounds") as u32)?,
+        part_two(&monkeys, params.get("rounds_two") as u32)?
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let monkeys = parse(input)?;
+    let printed = print(&monkeys);
+    printed_back(&monkeys, parse(&printed))?;
+    Ok(printed)
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum BinOp {
     Add,
     Mul,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Rhs {
     Imm(i64),
     Old,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Operation {
     op: BinOp,
     rhs: Rhs,
 }
 
 impl Operation {
-    fn apply(self, worry: i64) -> i64 {
+    const ARITH: Arith = Arith::new(11, "Operation::apply");
+
+    fn apply(self, worry: i64) -> Result<i64, Overflow> {
         let r = match self.rhs {
             Rhs::Imm(i) => i,
             Rhs::Old => worry,
         };
 
         match self.op {
-            BinOp::Add => worry + r,
-            BinOp::Mul => worry * r,
+            BinOp::Add => Self::ARITH.add(worry, r),
+            BinOp::Mul => Self::ARITH.mul(worry, r),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[non_exhaustive]
 enum TestOp {
     Divisible(i64),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Test {
     op: TestOp,
     if_true: i64,
@@ -71,7 +107,7 @@ impl Test {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct Monkey {
     _id: i64,
     items: Vec<i64>,
@@ -80,7 +116,7 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn inspect(&self, worry: i64) -> i64 {
+    fn inspect(&self, worry: i64) -> Result<i64, Overflow> {
         self.operation.apply(worry)
     }
 
@@ -89,16 +125,47 @@ impl Monkey {
     }
 }
 
-fn parse(input: &str) -> Vec<Monkey> {
-    let (remain, monkeys) = p_monkeys(input).expect("valid input");
-    if !remain.is_empty() {
-        panic!("incomplete parse: {}", remain);
+impl Display for Monkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.operation.op {
+            BinOp::Add => '+',
+            BinOp::Mul => '*',
+        };
+        let rhs = match self.operation.rhs {
+            Rhs::Imm(i) => i.to_string(),
+            Rhs::Old => "old".to_owned(),
+        };
+        let TestOp::Divisible(arg) = self.test.op;
+
+        writeln!(f, "Monkey {}:", self._id)?;
+        writeln!(f, "  Starting items: {}", self.items.iter().join(", "))?;
+        writeln!(f, "  Operation: new = old {} {}", op, rhs)?;
+        writeln!(f, "  Test: divisible by {}", arg)?;
+        writeln!(f, "    If true: throw to monkey {}", self.test.if_true)?;
+        write!(f, "    If false: throw to monkey {}", self.test.if_false)
     }
+}
+
+fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let (_, monkeys) = all_consuming(p_monkeys)(input)?;
+
     for (i, m) in monkeys.iter().enumerate() {
-        assert_eq!(m._id, i as i64)
+        if m._id != i as i64 {
+            return Err(ParseError::new(format!("monkey {} out of order", m._id)));
+        }
+
+        let TestOp::Divisible(arg) = m.test.op;
+        let in_range = |target: i64| (0..monkeys.len() as i64).contains(&target);
+        if arg == 0 || !in_range(m.test.if_true) || !in_range(m.test.if_false) {
+            return Err(ParseError::new(format!("test of monkey {}", m._id)));
+        }
     }
 
-    monkeys
+    Ok(monkeys)
+}
+
+fn print(monkeys: &[Monkey]) -> String {
+    monkeys.iter().join("\n\n")
 }
 
 fn p_monkeys(input: &str) -> IResult<&str, Vec<Monkey>> {
@@ -135,18 +202,17 @@ fn p_monkey(input: &str) -> IResult<&str, Monkey> {
 }
 
 fn p_operation(input: &str) -> IResult<&str, Operation> {
-    let (input, (op, digits_or_old)) =
-        separated_pair(one_of("+*"), space1, digit1.or(tag("old")))(input)?;
+    let (input, (op, rhs)) = separated_pair(
+        one_of("+*"),
+        space1,
+        p_unsigned.map(Rhs::Imm).or(tag("old").map(|_| Rhs::Old)),
+    )(input)?;
 
     let binop = match op {
         '+' => BinOp::Add,
         '*' => BinOp::Mul,
         _ => unreachable!("filtered by parser"),
     };
-    let rhs = match digits_or_old {
-        "old" => Rhs::Old,
-        _ => Rhs::Imm(digits_or_old.parse().unwrap()),
-    };
     let operation = Operation { op: binop, rhs };
 
     Ok((input, operation))
@@ -164,8 +230,7 @@ fn p_testop(input: &str) -> IResult<&str, TestOp> {
 }
 
 fn p_unsigned(input: &str) -> IResult<&str, i64> {
-    let (input, unsigned) = digit1(input)?;
-    Ok((input, unsigned.parse().unwrap()))
+    map_res(digit1, str::parse)(input)
 }
 
 struct MonkeyState {
@@ -182,7 +247,7 @@ impl MonkeyState {
     }
 }
 
-fn monkey_business(monkeys: &[Monkey], round: u32, relief: bool) -> i64 {
+fn monkey_business(monkeys: &[Monkey], round: u32, relief: bool) -> Result<i64, Overflow> {
     let mut states: Vec<MonkeyState> = monkeys.iter().map(MonkeyState::new).collect();
     // all tests are divisibility by a prime number
     let modulo = monkeys
@@ -203,7 +268,7 @@ fn monkey_business(monkeys: &[Monkey], round: u32, relief: bool) -> i64 {
             for t in 0..item_len {
                 let worry = states[i].items[t];
                 let new_worry = {
-                    let w = monkeys[i].inspect(worry);
+                    let w = monkeys[i].inspect(worry)?;
                     if relief {
                         w / 3
                     } else {
@@ -219,15 +284,18 @@ fn monkey_business(monkeys: &[Monkey], round: u32, relief: bool) -> i64 {
     }
 
     states.sort_by_key(|s| s.inspected);
-    states[states.len() - 1].inspected * states[states.len() - 2].inspected
+    Arith::new(11, "monkey_business").mul(
+        states[states.len() - 1].inspected,
+        states[states.len() - 2].inspected,
+    )
 }
 
-fn part_one(monkeys: &[Monkey]) -> i64 {
-    monkey_business(monkeys, 20, true)
+fn part_one(monkeys: &[Monkey], rounds: u32) -> Result<i64, Overflow> {
+    monkey_business(monkeys, rounds, true)
 }
 
-fn part_two(monkeys: &[Monkey]) -> i64 {
-    monkey_business(monkeys, 10000, false)
+fn part_two(monkeys: &[Monkey], rounds: u32) -> Result<i64, Overflow> {
+    monkey_business(monkeys, rounds, false)
 }
 
 #[cfg(test)]
@@ -264,13 +332,35 @@ Monkey 3:
 
     #[test]
     fn example_part_one() {
-        let monkeys = parse(INPUT);
-        assert_eq!(part_one(&monkeys), 10605);
+        let monkeys = parse(INPUT).unwrap();
+        assert_eq!(part_one(&monkeys, 20), Ok(10605));
     }
 
     #[test]
     fn example_part_two() {
-        let monkeys = parse(INPUT);
-        assert_eq!(part_two(&monkeys), 2713310158);
+        let monkeys = parse(INPUT).unwrap();
+        assert_eq!(part_two(&monkeys, 10000), Ok(2713310158));
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn squared_worry_overflow() {
+        let input = "Monkey 0:
+  Starting items: 100000
+  Operation: new = old * old
+  Test: divisible by 2
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 3
+    If true: throw to monkey 0
+    If false: throw to monkey 0";
+        let error = solution(input, &Params::new(PARAMS)).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("day 11: overflow in Operation::apply computing"));
     }
 }
@@ -1,71 +1,86 @@
-use std::collections::{hash_map::Entry, HashMap, VecDeque};
+use std::collections::{hash_map::Entry, VecDeque};
 
-use crate::utils::{Coord, DOWN, LEFT, RIGHT, UP};
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    timing::{phase, PART_ONE, PART_TWO, PREPROCESS},
+    utils::{Coord, FastMap, Grid},
+};
 
-pub fn solution(input: &str) -> String {
-    let map = parse(input);
-    format!("{}, {}", part_one(&map), part_two(&map))
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let map = parse(input)?;
+    let shortest = phase(PREPROCESS, || shortest_to_all(&map));
+    Ok(format!(
+        "{}, {}",
+        phase(PART_ONE, || part_one(&map, &shortest))?,
+        phase(PART_TWO, || part_two(&map, &shortest))?
+    ))
 }
 
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let map = parse(input)?;
+    let printed = print(&map);
+    printed_back(&map, parse(&printed))?;
+    Ok(printed)
+}
+
+#[derive(PartialEq)]
 struct Map {
-    grid: Vec<Vec<i8>>,
+    grid: Grid<i8>,
     start: Coord,
     end: Coord,
 }
 
 impl Map {
-    fn get_height(&self, Coord { x, y }: Coord) -> Option<i8> {
-        self.grid.get(y as usize)?.get(x as usize).copied()
+    fn get_height(&self, coord: Coord) -> Option<i8> {
+        self.grid.get(coord).copied()
     }
 
     fn reverse_next_square(&self, from: Coord) -> impl Iterator<Item = Coord> + '_ {
-        [UP, DOWN, LEFT, RIGHT]
-            .into_iter()
-            .map(move |d| from + d)
-            .filter(move |&pos| {
-                let Some(h) = self.get_height(from) else {
-                    return false;
-                };
-                let Some(h_next) = self.get_height(pos) else {
-                    return false;
-                };
-                h_next - h >= -1
-            })
+        let h = self.grid[from];
+        self.grid
+            .neighbors4(from)
+            .filter(move |&pos| self.grid[pos] - h >= -1)
     }
 }
 
-fn parse(input: &str) -> Map {
-    let mut start = Coord::default();
-    let mut end = Coord::default();
-
-    let grid = input
-        .lines()
-        .enumerate()
-        .map(|(col, l)| {
-            l.as_bytes()
-                .iter()
-                .enumerate()
-                .map(|(row, b)| match b {
-                    b'S' => {
-                        start = Coord::new(row as i64, col as i64);
-                        0
-                    }
-                    b'E' => {
-                        end = Coord::new(row as i64, col as i64);
-                        (b'z' - b'a') as i8
-                    }
-                    b'a'..=b'z' => (b - b'a') as i8,
-                    _ => unreachable!("invalid height"),
-                })
-                .collect()
-        })
-        .collect();
+fn parse(input: &str) -> Result<Map, ParseError> {
+    let mut start = None;
+    let mut end = None;
 
-    Map { grid, start, end }
+    let grid = Grid::parse(input, |coord, c| match c {
+        'S' => {
+            start = Some(coord);
+            Ok(0)
+        }
+        'E' => {
+            end = Some(coord);
+            Ok((b'z' - b'a') as i8)
+        }
+        'a'..='z' => Ok((c as u8 - b'a') as i8),
+        _ => Err(ParseError::new(format!("height: {:?}", c))),
+    })?;
+
+    let start = start.ok_or_else(|| ParseError::new("no start square"))?;
+    let end = end.ok_or_else(|| ParseError::new("no best signal square"))?;
+
+    Ok(Map { grid, start, end })
+}
+
+fn print(map: &Map) -> String {
+    let mut squares = map.grid.map(|&h| (b'a' + h as u8) as char);
+    squares[map.start] = 'S';
+    squares[map.end] = 'E';
+    squares.to_string()
 }
 
-fn shortest_to_all(map: &Map) -> HashMap<Coord, (Coord, isize)> {
-    let mut shortest = HashMap::new();
+fn shortest_to_all(map: &Map) -> FastMap<Coord, (Coord, isize)> {
+    let mut shortest = FastMap::default();
     shortest.insert(map.end, (map.end, 0));
     let mut queue = VecDeque::new();
     queue.push_back((map.end, 0));
@@ -83,17 +98,19 @@ fn shortest_to_all(map: &Map) -> HashMap<Coord, (Coord, isize)> {
     shortest
 }
 
-fn part_one(map: &Map) -> isize {
-    let shortest = shortest_to_all(map);
-    let (_, cost) = shortest[&map.start];
-    cost
+fn no_path() -> ParseError {
+    ParseError::new("no path to the best signal square")
+}
+
+fn part_one(map: &Map, shortest: &FastMap<Coord, (Coord, isize)>) -> Result<isize, ParseError> {
+    let (_, cost) = shortest.get(&map.start).ok_or_else(no_path)?;
+    Ok(*cost)
 }
 
-fn part_two(map: &Map) -> isize {
-    let shortest = shortest_to_all(map);
+fn part_two(map: &Map, shortest: &FastMap<Coord, (Coord, isize)>) -> Result<isize, ParseError> {
     shortest
-        .into_iter()
-        .filter_map(|(p, (_, c))| {
+        .iter()
+        .filter_map(|(&p, &(_, c))| {
             if map.get_height(p) == Some(0) {
                 Some(c)
             } else {
@@ -101,5 +118,5 @@ fn part_two(map: &Map) -> isize {
             }
         })
         .min()
-        .expect("non-empty grid")
+        .ok_or_else(no_path)
 }
@@ -1,5 +1,6 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt::Display};
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -10,9 +11,45 @@ use nom::{
     IResult, Parser,
 };
 
-pub fn solution(input: &str) -> String {
-    let pairs = parse(input);
-    format!("{}, {}", part_one(&pairs), part_two(&pairs))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let pairs = parse(input)?;
+    Ok(format!("{}, {}", part_one(&pairs), part_two(&pairs)))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let pairs = parse(input)?;
+    let printed = print(&pairs);
+    printed_back(&pairs, parse(&printed))?;
+    Ok(printed)
+}
+
+/// `size` pairs of packets nested a few lists deep.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    fn list(depth: usize, rng: &mut Rng) -> Packet {
+        let packets = (0..rng.range(0, 4))
+            .map(|_| match rng.below(3) {
+                0 if depth > 0 => list(depth - 1, rng),
+                _ => Packet::Int(rng.range(0, 10)),
+            })
+            .collect();
+        Packet::List(packets)
+    }
+
+    let pairs: Vec<(Packet, Packet)> = (0..size.max(1))
+        .map(|_| (list(3, rng), list(3, rng)))
+        .collect();
+    print(&pairs)
 }
 
 #[derive(Debug, Clone)]
@@ -46,8 +83,25 @@ impl Ord for Packet {
     }
 }
 
-fn parse(input: &str) -> Vec<(Packet, Packet)> {
-    all_consuming(p_pairs)(input).expect("valid input").1
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::List(packets) => write!(f, "[{}]", packets.iter().join(",")),
+            Packet::Int(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    let (_, pairs) = all_consuming(p_pairs)(input)?;
+    Ok(pairs)
+}
+
+fn print(pairs: &[(Packet, Packet)]) -> String {
+    pairs
+        .iter()
+        .map(|(fst, snd)| format!("{}\n{}", fst, snd))
+        .join("\n\n")
 }
 
 fn p_pairs(input: &str) -> IResult<&str, Vec<(Packet, Packet)>> {
@@ -116,13 +170,13 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let pairs = parse(INPUT);
+        let pairs = parse(INPUT).unwrap();
         assert_eq!(part_one(&pairs), 13);
     }
 
     #[test]
     fn example_part_two() {
-        let pairs = parse(INPUT);
+        let pairs = parse(INPUT).unwrap();
         assert_eq!(part_two(&pairs), 140);
     }
 }
@@ -1,22 +1,58 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{hash_map::RandomState, HashSet, VecDeque},
+    hash::BuildHasher,
     iter::from_fn,
 };
 
 use nom::{
-    bytes::complete::tag,
-    character::complete::{i64, line_ending},
-    combinator::all_consuming,
-    multi::separated_list1,
-    sequence::separated_pair,
-    IResult, Parser,
+    bytes::complete::tag, character::complete::line_ending, combinator::all_consuming,
+    multi::separated_list1, IResult, Parser,
 };
 
-use crate::utils::{Coord, DOWN, LEFT, RIGHT};
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::{p_point, Coord, FastState, DOWN, LEFT, RIGHT},
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let paths = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one::<FastState>(&paths),
+        part_two::<FastState>(&paths)
+    ))
+}
+
+pub fn solution_grain_by_grain(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let paths = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one::<FastState>(&paths),
+        part_two_grain_by_grain::<FastState>(&paths)
+    ))
+}
 
-pub fn solution(input: &str) -> String {
-    let paths = parse(input);
-    format!("{}, {}", part_one(&paths), part_two(&paths))
+/// The solution with the cave hashed by the standard SipHash, to compare against.
+pub fn solution_siphash(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let paths = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one::<RandomState>(&paths),
+        part_two::<RandomState>(&paths)
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let paths = parse(input)?;
+    let printed = print(&paths);
+    printed_back(&paths, parse(&printed))?;
+    Ok(printed)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -53,6 +89,7 @@ impl Line {
     }
 }
 
+#[derive(PartialEq)]
 struct Path {
     vertices: Vec<Coord>,
 }
@@ -69,15 +106,56 @@ impl Path {
     }
 }
 
-fn parse(input: &str) -> Vec<Path> {
-    let (_, paths) = all_consuming(p_paths)(input).expect("valid complete parse");
-    // orthogonal lines
-    assert!(paths.iter().all(|p| p.lines().all(|l| {
-        let (x0, y0) = l.from.to_tuple();
-        let (x1, y1) = l.to.to_tuple();
-        x0 == x1 || y0 == y1
-    })));
+fn parse(input: &str) -> Result<Vec<Path>, ParseError> {
+    let (_, paths) = all_consuming(p_paths)(input)?;
+    let orthogonal = paths.iter().all(|p| {
+        p.lines().all(|l| {
+            let (x0, y0) = l.from.to_tuple();
+            let (x1, y1) = l.to.to_tuple();
+            x0 == x1 || y0 == y1
+        })
+    });
+
+    if !orthogonal {
+        Err(ParseError::new("rock paths: diagonal line"))
+    } else if paths.iter().any(|p| p.vertices.len() < 2) {
+        Err(ParseError::new("rock paths: single vertex"))
+    } else if paths
+        .iter()
+        .flat_map(|p| &p.vertices)
+        .any(|v| !(0..=MAX_COORD).contains(&v.x()) || !(0..=MAX_COORD).contains(&v.y()))
+    {
+        Err(ParseError::new(format!(
+            "rock paths: coordinate outside 0 to {}",
+            MAX_COORD
+        )))
+    } else if paths
+        .iter()
+        .flat_map(|p| p.lines())
+        .flat_map(|l| l.rocks())
+        .any(|r| r == START)
+    {
+        Err(ParseError::new("rock paths: rock on the sand source"))
+    } else {
+        Ok(paths)
+    }
+}
+
+fn print(paths: &[Path]) -> String {
     paths
+        .iter()
+        .map(|p| {
+            p.vertices
+                .iter()
+                .map(|v| {
+                    let (x, y) = v.to_tuple();
+                    format!("{},{}", x, y)
+                })
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn p_paths(input: &str) -> IResult<&str, Vec<Path>> {
@@ -85,36 +163,34 @@ fn p_paths(input: &str) -> IResult<&str, Vec<Path>> {
 }
 
 fn p_path(input: &str) -> IResult<&str, Path> {
-    separated_list1(tag(" -> "), p_coord)
+    separated_list1(tag(" -> "), p_point)
         .map(|vertices| Path { vertices })
         .parse(input)
 }
 
-fn p_coord(input: &str) -> IResult<&str, Coord> {
-    separated_pair(i64, tag(","), i64)
-        .map(|(x, y)| Coord { x, y })
-        .parse(input)
-}
-
-struct Cave {
-    rocks: HashSet<Coord>,
-    sands: HashSet<Coord>,
+struct Cave<S> {
+    rocks: HashSet<Coord, S>,
+    sands: HashSet<Coord, S>,
     deepest: i64,
 }
 
-impl Cave {
+impl<S: BuildHasher + Default> Cave<S> {
     fn new(paths: &[Path]) -> Self {
-        let rocks: HashSet<_> = paths
+        let rocks: HashSet<_, S> = paths
             .iter()
             .flat_map(|p| p.lines())
             .flat_map(|l| l.rocks())
             .collect();
 
-        let deepest = rocks.iter().map(|c| c.y).max().unwrap();
+        let deepest = rocks
+            .iter()
+            .map(|c| c.y())
+            .max()
+            .expect("paths of two vertices or more");
 
         Self {
             rocks,
-            sands: HashSet::new(),
+            sands: HashSet::default(),
             deepest,
         }
     }
@@ -128,7 +204,7 @@ impl Cave {
     }
 
     fn abysmal(&self, sand: Coord) -> bool {
-        sand.y >= self.deepest
+        sand.y() >= self.deepest
     }
 
     fn contains(&self, coord: &Coord) -> bool {
@@ -148,12 +224,16 @@ impl Cave {
     }
 }
 
-const START: Coord = Coord { x: 500, y: 0 };
+const START: Coord = Coord::new(500, 0);
+
+/// Keeps the cave small enough to simulate, puzzle inputs stay within a few hundred.
+const MAX_COORD: i64 = 1000;
 
-fn part_one(paths: &[Path]) -> i64 {
-    let mut cave = Cave::new(paths);
+fn part_one<S: BuildHasher + Default>(paths: &[Path]) -> i64 {
+    let mut cave = Cave::<S>::new(paths);
     let mut cnt = 0;
-    loop {
+    // rock may stretch wide enough to catch all sand until it blocks the source
+    while !cave.contains(&START) {
         let mut sand = START;
         while let Some(dir) = cave.dir(sand) {
             sand = sand + dir;
@@ -164,22 +244,23 @@ fn part_one(paths: &[Path]) -> i64 {
         cave.rest(sand);
         cnt += 1;
     }
+    cnt
 }
 
-fn part_two(paths: &[Path]) -> usize {
-    let cave = Cave::new(paths);
+fn part_two<S: BuildHasher + Default>(paths: &[Path]) -> usize {
+    let cave = Cave::<S>::new(paths);
     let mut cnt = 1;
 
     let mut scan_line: VecDeque<bool> = VecDeque::new();
     scan_line.push_front(true);
-    let mut start = START.x;
+    let mut start = START.x();
 
     // assume coordinate (x, y) will be filled with resting sand
     // 1.   coordinate (x, y + 1) is rock or eventually will be filled with resting sand
     // 2.   because of 1, coordinate (x - 1, y + 1) is rock or eventually will be filled
     // 3.   because of 1, coordinate (x + 1, y + 1) is rock or eventually will be filled
     // 4.   by rules, no other space on depth y will be filled by sand falling from (x, y)
-    for y in START.y + 1..cave.floor() {
+    for y in START.y() + 1..cave.floor() {
         let mut next_line = scan_line.clone();
         next_line.push_back(false);
         next_line.push_front(false);
@@ -193,7 +274,7 @@ fn part_two(paths: &[Path]) -> usize {
                 *b = *b || scan_line[i];
             }
             let x = i as i64 + start;
-            *b = *b && !cave.contains(&Coord { x, y });
+            *b = *b && !cave.contains(&Coord::new(x, y));
         }
 
         cnt += next_line.iter().filter(|b| **b).count();
@@ -203,6 +284,25 @@ fn part_two(paths: &[Path]) -> usize {
     cnt
 }
 
+fn part_two_grain_by_grain<S: BuildHasher + Default>(paths: &[Path]) -> usize {
+    let mut cave = Cave::<S>::new(paths);
+    let mut cnt = 0;
+
+    while !cave.contains(&START) {
+        let mut sand = START;
+        while let Some(dir) = cave.dir(sand) {
+            if sand.y() + 1 == cave.floor() {
+                break;
+            }
+            sand = sand + dir;
+        }
+        cave.rest(sand);
+        cnt += 1;
+    }
+
+    cnt
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,7 +312,7 @@ mod tests {
 
     #[test]
     fn len_and_rocks_match_in_number() {
-        let paths = parse(INPUT);
+        let paths = parse(INPUT).unwrap();
         let len_sum = paths
             .iter()
             .flat_map(|p| p.lines())
@@ -228,13 +328,27 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let paths = parse(INPUT);
-        assert_eq!(part_one(&paths), 24);
+        let paths = parse(INPUT).unwrap();
+        assert_eq!(part_one::<FastState>(&paths), 24);
     }
 
     #[test]
     fn example_part_two() {
-        let paths = parse(INPUT);
-        assert_eq!(part_two(&paths), 93);
+        let paths = parse(INPUT).unwrap();
+        assert_eq!(part_two::<FastState>(&paths), 93);
+        assert_eq!(part_two_grain_by_grain::<FastState>(&paths), 93);
+    }
+
+    #[test]
+    fn rocks_around_the_source() {
+        assert!(parse("498,-4 -> 498,-2\n490,-3 -> 492,-3").is_err());
+        assert!(parse("499,0 -> 501,0").is_err());
+        assert!(parse("498,99999994 -> 498,6").is_err());
+
+        let paths = parse("490,0 -> 495,0").unwrap();
+        assert_eq!(
+            part_two::<FastState>(&paths),
+            part_two_grain_by_grain::<FastState>(&paths)
+        );
     }
 }
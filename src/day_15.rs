@@ -10,17 +10,39 @@ use nom::{
     IResult, Parser,
 };
 
-use crate::utils::{Closed, Coord};
+use crate::{
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    utils::{Closed, Coord},
+};
+
+pub const PARAMS: &[Param] = &[
+    Param::new("row", 2_000_000),
+    Param::new("bound", 4_000_000).at_least(0),
+];
 
-pub fn solution(input: &str) -> String {
-    let reports = parse(input);
-    format!(
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let reports = parse(input)?;
+    Ok(format!(
         "{}, {}",
-        part_one(&reports, 2_000_000),
-        path_two(&reports, 0, 4_000_000)
-    )
+        part_one(&reports, params.get("row")),
+        path_two(&reports, 0, params.get("bound"))?
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let reports = parse(input)?;
+    let printed = print(&reports);
+    printed_back(&reports, parse(&printed))?;
+    Ok(printed)
 }
 
+#[derive(PartialEq)]
 struct Report {
     sensor: Coord,
     beacon: Coord,
@@ -37,11 +59,11 @@ impl Report {
 
     fn cover_at(&self, y: i64) -> Option<Closed> {
         let d_beacon = self.sensor.manhattan_distance(self.beacon);
-        let d_y = (self.sensor.y - y).abs();
+        let d_y = (self.sensor.y() - y).abs();
         let r = d_beacon - d_y;
 
         if r >= 0 {
-            Some(Closed::new(self.sensor.x - r, self.sensor.x + r))
+            Some(Closed::new(self.sensor.x() - r, self.sensor.x() + r))
         } else {
             None
         }
@@ -104,9 +126,24 @@ impl Line {
     }
 }
 
-fn parse(input: &str) -> Vec<Report> {
-    let (_, reports) = all_consuming(p_reports)(input).expect("valid complete parse");
+fn parse(input: &str) -> Result<Vec<Report>, ParseError> {
+    let (_, reports) = all_consuming(p_reports)(input)?;
+    Ok(reports)
+}
+
+fn print(reports: &[Report]) -> String {
     reports
+        .iter()
+        .map(|r| {
+            let (sx, sy) = r.sensor.to_tuple();
+            let (bx, by) = r.beacon.to_tuple();
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+                sx, sy, bx, by
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn p_reports(input: &str) -> IResult<&str, Vec<Report>> {
@@ -137,15 +174,15 @@ fn cover_at(reports: &[Report], y: i64) -> Vec<Closed> {
     let mut covers: Vec<Closed> = reports.iter().filter_map(|r| r.cover_at(y)).collect();
     covers.sort_by_key(|c| c.start);
 
-    let mut i = 0;
-    let mut connected: Vec<Closed> = vec![covers[0]];
+    let mut connected: Vec<Closed> = vec![];
 
-    for cover in covers.into_iter().skip(1) {
-        if let Some(c) = connected[i].connect(cover) {
-            connected[i] = c;
-        } else {
-            connected.push(cover);
-            i += 1;
+    for cover in covers {
+        match connected.last_mut() {
+            Some(last) => match last.connect(cover) {
+                Some(c) => *last = c,
+                None => connected.push(cover),
+            },
+            None => connected.push(cover),
         }
     }
 
@@ -159,26 +196,26 @@ fn part_one(reports: &[Report], y: i64) -> i64 {
     let beacons = reports
         .iter()
         .map(|r| r.beacon)
-        .filter(|b| b.y == y)
+        .filter(|b| b.y() == y)
         .collect::<HashSet<_>>();
     cover_len - beacons.len() as i64
 }
 
-fn path_two(reports: &[Report], min: i64, max: i64) -> i64 {
+fn path_two(reports: &[Report], min: i64, max: i64) -> Result<i64, ParseError> {
     let (anti, orth): (Vec<_>, Vec<_>) = reports.iter().flat_map(|r| r.edges()).partition(|s| s.a);
 
     let uncovered = iproduct!(anti, orth)
         .filter_map(|(s0, s1)| s0.intersect(&s1))
         .find(|c| {
-            c.x >= min
-                && c.x <= max
-                && c.y >= min
-                && c.y <= max
+            c.x() >= min
+                && c.x() <= max
+                && c.y() >= min
+                && c.y() <= max
                 && reports.iter().all(|r| !r.contains(*c))
         })
-        .unwrap();
+        .ok_or_else(|| ParseError::new("no position left for the distress beacon"))?;
 
-    uncovered.x * 4_000_000 + uncovered.y
+    Ok(uncovered.x() * 4_000_000 + uncovered.y())
 }
 
 #[cfg(test)]
@@ -202,7 +239,7 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn complete_parse() {
-        parse(INPUT);
+        parse(INPUT).unwrap();
     }
 
     #[test]
@@ -217,13 +254,13 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn example_part_one() {
-        let reports = parse(INPUT);
+        let reports = parse(INPUT).unwrap();
         assert_eq!(part_one(&reports, 10), 26);
     }
 
     #[test]
     fn example_part_two() {
-        let reports = parse(INPUT);
-        assert_eq!(path_two(&reports, 0, 20), 56000011);
+        let reports = parse(INPUT).unwrap();
+        assert_eq!(path_two(&reports, 0, 20).unwrap(), 56000011);
     }
 }
@@ -1,8 +1,10 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashSet},
+    fmt::{self, Display},
     hash::Hash,
 };
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take},
@@ -12,27 +14,118 @@ use nom::{
     sequence::preceded,
     IResult,
 };
-use pathfinding::prelude::{astar, dijkstra_all};
+use pathfinding::prelude::dijkstra_all;
+
+use crate::{
+    checkpoint::{self, Checkpoint, Checkpoints, Snapshot},
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    search::astar,
+    timing::{phase, PART_ONE, PART_TWO, PREPROCESS},
+    utils::{BitSet, FastMap, Rng},
+};
 
-use crate::utils::BitSet;
+pub const PARAMS: &[Param] = &[checkpoint::PARAM];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let valves = parse(input)?;
+    let tunnels = phase(PREPROCESS, || Tunnels::new(&valves));
+    let checkpoints = Checkpoints::from_params(input, params);
+    Ok(format!(
+        "{}, {}",
+        phase(PART_ONE, || part_one(&tunnels, checkpoints.as_ref())),
+        phase(PART_TWO, || part_two(&tunnels, checkpoints.as_ref()))
+    ))
+}
 
-pub fn solution(input: &str) -> String {
-    let valves = parse(input);
-    let compressed = compress(&valves);
-    let (ids, start) = identifiers(&compressed);
-    format!("{}, {}", part_one(&ids, start), part_two(&ids, start))
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
 }
 
-#[derive(Debug)]
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let valves = parse(input)?;
+    let printed = print(&valves);
+    printed_back(&valves, parse(&printed))?;
+    Ok(printed)
+}
+
+/// A cave of about `size` valves joined as a tree, at most the 676 that two letters can name. Only
+/// a few of them have a flow rate, as in the puzzle.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    const FLOWING: usize = 6;
+
+    let names: Vec<String> = (0..size.clamp(2, 26 * 26))
+        .map(|i| [b'A' + (i / 26) as u8, b'A' + (i % 26) as u8])
+        .map(|name| String::from_utf8_lossy(&name).into_owned())
+        .collect();
+    let mut exits = vec![vec![]; names.len()];
+    for valve in 1..names.len() {
+        let other = rng.below(valve as u64) as usize;
+        exits[valve].push(names[other].as_str());
+        exits[other].push(names[valve].as_str());
+    }
+
+    let mut valves: Vec<Valve> = names
+        .iter()
+        .zip(exits)
+        .map(|(name, exit)| Valve {
+            name,
+            flow: 0,
+            exit,
+        })
+        .collect();
+    for _ in 0..FLOWING {
+        let valve = rng.below(valves.len() as u64) as usize;
+        valves[valve].flow = rng.range(1, 25);
+    }
+    print(&valves)
+}
+
+#[derive(Debug, PartialEq, Eq)]
 struct Valve<'a> {
     name: &'a str,
     flow: i64,
     exit: Vec<&'a str>,
 }
 
-fn parse(input: &str) -> Vec<Valve> {
-    let (_, valves) = all_consuming(p_valves)(input).expect("valid complete parse");
-    valves
+impl Display for Valve<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tunnels = if self.exit.len() == 1 {
+            "tunnel leads to valve"
+        } else {
+            "tunnels lead to valves"
+        };
+        write!(
+            f,
+            "Valve {} has flow rate={}; {} {}",
+            self.name,
+            self.flow,
+            tunnels,
+            self.exit.iter().join(", ")
+        )
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Valve<'_>>, ParseError> {
+    let (_, valves) = all_consuming(p_valves)(input)?;
+
+    let names: HashSet<&str> = valves.iter().map(|v| v.name).collect();
+    if !names.contains(START) {
+        return Err(ParseError::new(format!("no valve {}", START)));
+    }
+    if let Some(exit) = valves
+        .iter()
+        .flat_map(|v| &v.exit)
+        .find(|e| !names.contains(*e))
+    {
+        return Err(ParseError::new(format!("tunnel to unknown valve {}", exit)));
+    }
+    Ok(valves)
+}
+
+fn print(valves: &[Valve]) -> String {
+    valves.iter().join("\n")
 }
 
 #[derive(Debug)]
@@ -43,13 +136,13 @@ struct CompressedValve<'a> {
 }
 
 struct IdCache<T> {
-    cache: HashMap<T, usize>,
+    cache: FastMap<T, usize>,
 }
 
 impl<T: PartialEq + Eq + Hash> IdCache<T> {
     fn new() -> Self {
         Self {
-            cache: HashMap::new(),
+            cache: FastMap::default(),
         }
     }
 
@@ -65,9 +158,9 @@ impl<T: PartialEq + Eq + Hash> IdCache<T> {
     }
 }
 
-fn compress<'a>(valves: &'a [Valve]) -> HashMap<&'a str, CompressedValve<'a>> {
-    let valves: HashMap<&str, &Valve> = valves.iter().map(|v| (v.name, v)).collect();
-    let mut compressed = HashMap::new();
+fn compress<'a>(valves: &'a [Valve]) -> FastMap<&'a str, CompressedValve<'a>> {
+    let valves: FastMap<&str, &Valve> = valves.iter().map(|v| (v.name, v)).collect();
+    let mut compressed = FastMap::default();
 
     for &k in valves.keys().filter(|&&k| k == START || valves[k].flow > 0) {
         let predecessors = dijkstra_all(&k, |n| {
@@ -105,7 +198,7 @@ struct IdValve {
     exit: Vec<(usize, i64)>,
 }
 
-fn identifiers(valves: &HashMap<&str, CompressedValve>) -> (HashMap<usize, IdValve>, usize) {
+fn identifiers(valves: &FastMap<&str, CompressedValve>) -> (FastMap<usize, IdValve>, usize) {
     let mut cache = IdCache::new();
 
     for &name in valves.keys() {
@@ -131,11 +224,37 @@ fn identifiers(valves: &HashMap<&str, CompressedValve>) -> (HashMap<usize, IdVal
     (id_valves, start)
 }
 
-fn p_valves(input: &str) -> IResult<&str, Vec<Valve>> {
+/// The valves worth opening, shared by both parts.
+struct Tunnels {
+    valves: FastMap<usize, IdValve>,
+    start: usize,
+    /// Valves by decreasing flow, for the heuristics.
+    sorted_by_flow: Vec<(usize, i64)>,
+}
+
+impl Tunnels {
+    fn new(valves: &[Valve]) -> Self {
+        let compressed = compress(valves);
+        let (valves, start) = identifiers(&compressed);
+
+        let mut sorted_by_flow: Vec<(usize, i64)> =
+            valves.values().map(|v| (v.name, v.flow)).collect();
+        sorted_by_flow.sort_by_key(|(_, f)| *f);
+        sorted_by_flow.reverse();
+
+        Self {
+            valves,
+            start,
+            sorted_by_flow,
+        }
+    }
+}
+
+fn p_valves(input: &str) -> IResult<&str, Vec<Valve<'_>>> {
     separated_list1(line_ending, p_valve)(input)
 }
 
-fn p_valve(input: &str) -> IResult<&str, Valve> {
+fn p_valve(input: &str) -> IResult<&str, Valve<'_>> {
     let (input, name) = preceded(tag("Valve "), take(2usize))(input)?;
     let (input, flow) = preceded(tag(" has flow rate="), i64)(input)?;
     let (input, exit) = preceded(
@@ -157,7 +276,7 @@ struct State {
 }
 
 impl State {
-    fn new(valves: &HashMap<usize, IdValve>, start: usize) -> Self {
+    fn new(valves: &FastMap<usize, IdValve>, start: usize) -> Self {
         let mut opened = BitSet::new();
         if valves[&start].flow == 0 {
             opened.insert(start);
@@ -174,7 +293,7 @@ impl State {
         self.opened.contains(valve)
     }
 
-    fn moves(&self, valves: &HashMap<usize, IdValve>) -> Vec<(Self, i64)> {
+    fn moves(&self, valves: &FastMap<usize, IdValve>) -> Vec<(Self, i64)> {
         let mut nexts = vec![];
         let (dest, distance) = self.current;
 
@@ -204,7 +323,7 @@ impl State {
         nexts
     }
 
-    fn finished(&self, valves: &HashMap<usize, IdValve>) -> bool {
+    fn finished(&self, valves: &FastMap<usize, IdValve>) -> bool {
         debug_assert!(self.remaining >= 0);
         self.remaining == 0 || self.opened.len() == valves.len()
     }
@@ -235,7 +354,23 @@ impl State {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+impl Snapshot for State {
+    fn save(&self, fields: &mut Vec<i64>) {
+        let (dest, distance) = self.current;
+        fields.extend([self.remaining, dest as i64, distance]);
+        self.opened.save(fields);
+    }
+
+    fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self> {
+        Some(Self {
+            remaining: fields.next()?,
+            current: (fields.next()? as usize, fields.next()?),
+            opened: BitSet::restore(fields)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct StateWithElephant {
     remaining: i64,
     actors: [(usize, i64); 2],
@@ -243,7 +378,7 @@ struct StateWithElephant {
 }
 
 impl StateWithElephant {
-    fn new(valves: &HashMap<usize, IdValve>, start: usize) -> Self {
+    fn new(valves: &FastMap<usize, IdValve>, start: usize) -> Self {
         let mut opened = BitSet::new();
         if valves[&start].flow == 0 {
             opened.insert(start);
@@ -260,31 +395,31 @@ impl StateWithElephant {
         self.opened.contains(valve)
     }
 
-    fn finished(&self, valves: &HashMap<usize, IdValve>) -> bool {
+    fn finished(&self, valves: &FastMap<usize, IdValve>) -> bool {
         assert!(self.remaining >= 0);
         self.remaining == 0 || self.opened.len() == valves.len()
     }
 
-    fn actor_moves(&self, valves: &HashMap<usize, IdValve>, actor: usize) -> Vec<(Self, i64)> {
+    fn actor_moves(&self, valves: &FastMap<usize, IdValve>, actor: usize) -> Vec<(Self, i64)> {
         let mut nexts = vec![];
         let (dest, distance) = self.actors[actor];
 
         if distance > 0 {
-            let mut next = *self;
+            let mut next = self.clone();
             next.actors[actor] = (dest, distance - 1);
             nexts.push((next, 0));
             return nexts;
         }
 
         if !self.opened(dest) {
-            let mut next = *self;
+            let mut next = self.clone();
             next.opened.insert(dest);
             let cost = next.remaining * valves[&dest].flow;
             nexts.push((next, -cost));
         }
 
         for (next_dest, next_distance) in &valves[&dest].exit {
-            let mut next = *self;
+            let mut next = self.clone();
             next.actors[actor] = (*next_dest, next_distance - 1);
             nexts.push((next, 0));
         }
@@ -292,16 +427,16 @@ impl StateWithElephant {
         nexts
     }
 
-    fn you_moves(&self, valves: &HashMap<usize, IdValve>) -> Vec<(Self, i64)> {
+    fn you_moves(&self, valves: &FastMap<usize, IdValve>) -> Vec<(Self, i64)> {
         self.actor_moves(valves, 0)
     }
 
-    fn elephant_moves(&self, valves: &HashMap<usize, IdValve>) -> Vec<(Self, i64)> {
+    fn elephant_moves(&self, valves: &FastMap<usize, IdValve>) -> Vec<(Self, i64)> {
         self.actor_moves(valves, 1)
     }
 
-    fn moves(&self, valves: &HashMap<usize, IdValve>) -> Vec<(Self, i64)> {
-        let mut moved = *self;
+    fn moves(&self, valves: &FastMap<usize, IdValve>) -> Vec<(Self, i64)> {
+        let mut moved = self.clone();
         moved.remaining -= 1;
 
         moved
@@ -315,56 +450,85 @@ impl StateWithElephant {
             .collect()
     }
 
-    fn heuristic(&self, sorted_by_flow: &[(usize, i64)], valves: &HashMap<usize, IdValve>) -> i64 {
+    fn heuristic(&self, sorted_by_flow: &[(usize, i64)], valves: &FastMap<usize, IdValve>) -> i64 {
         self.actors
             .into_iter()
             .map(|actor| {
                 let state = State {
                     remaining: self.remaining,
                     current: actor,
-                    opened: self.opened,
+                    opened: self.opened.clone(),
                 };
-                one_actor_cost(&state, valves, sorted_by_flow)
+                one_actor_cost(&state, valves, sorted_by_flow, None)
             })
             .sum()
     }
 }
 
+impl Snapshot for StateWithElephant {
+    fn save(&self, fields: &mut Vec<i64>) {
+        fields.push(self.remaining);
+        for (dest, distance) in self.actors {
+            fields.extend([dest as i64, distance]);
+        }
+        self.opened.save(fields);
+    }
+
+    fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self> {
+        let remaining = fields.next()?;
+        let mut actors = [(0, 0); 2];
+        for actor in &mut actors {
+            *actor = (fields.next()? as usize, fields.next()?);
+        }
+        Some(Self {
+            remaining,
+            actors,
+            opened: BitSet::restore(fields)?,
+        })
+    }
+}
+
 fn one_actor_cost(
     state: &State,
-    valves: &HashMap<usize, IdValve>,
+    valves: &FastMap<usize, IdValve>,
     sorted_by_flow: &[(usize, i64)],
+    checkpoint: Option<&mut Checkpoint>,
 ) -> i64 {
-    let (_, cost) = astar(
+    astar(
         state,
         |state| state.moves(valves),
         |state| state.heuristic(sorted_by_flow),
         |state| state.finished(valves),
+        checkpoint,
     )
-    .unwrap();
-    cost
+    .expect("the search ends when time runs out")
 }
 
-fn part_one(valves: &HashMap<usize, IdValve>, start: usize) -> i64 {
-    let mut sorted_by_flow: Vec<(usize, i64)> = valves.values().map(|v| (v.name, v.flow)).collect();
-    sorted_by_flow.sort_by_key(|(_, f)| *f);
-    sorted_by_flow.reverse();
+fn part_one(tunnels: &Tunnels, checkpoints: Option<&Checkpoints>) -> i64 {
+    let mut checkpoint = checkpoints.map(|c| c.get("day_16_part_one"));
+    let cost = one_actor_cost(
+        &State::new(&tunnels.valves, tunnels.start),
+        &tunnels.valves,
+        &tunnels.sorted_by_flow,
+        checkpoint.as_mut(),
+    );
+    checkpoint.iter().for_each(Checkpoint::clear);
 
-    -one_actor_cost(&State::new(valves, start), valves, &sorted_by_flow)
+    -cost
 }
 
-fn part_two(valves: &HashMap<usize, IdValve>, start: usize) -> i64 {
-    let mut sorted_by_flow: Vec<(usize, i64)> = valves.values().map(|v| (v.name, v.flow)).collect();
-    sorted_by_flow.sort_by_key(|(_, f)| *f);
-    sorted_by_flow.reverse();
-
-    let (_, cost) = astar(
-        &StateWithElephant::new(valves, start),
+fn part_two(tunnels: &Tunnels, checkpoints: Option<&Checkpoints>) -> i64 {
+    let valves = &tunnels.valves;
+    let mut checkpoint = checkpoints.map(|c| c.get("day_16_part_two"));
+    let cost = astar(
+        &StateWithElephant::new(valves, tunnels.start),
         |state| state.moves(valves),
-        |state| state.heuristic(&sorted_by_flow, valves),
+        |state| state.heuristic(&tunnels.sorted_by_flow, valves),
         |state| state.finished(valves),
+        checkpoint.as_mut(),
     )
-    .unwrap();
+    .expect("the search ends when time runs out");
+    checkpoint.iter().for_each(Checkpoint::clear);
 
     -cost
 }
@@ -372,6 +536,7 @@ fn part_two(valves: &HashMap<usize, IdValve>, start: usize) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::{instrumented, Admissibility};
 
     const INPUT: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
 Valve BB has flow rate=13; tunnels lead to valves CC, AA
@@ -386,17 +551,61 @@ Valve JJ has flow rate=21; tunnel leads to valve II";
 
     #[test]
     fn example_part_one() {
-        let valves = parse(INPUT);
-        let compressed = compress(&valves);
-        let (ids, start) = identifiers(&compressed);
-        assert_eq!(part_one(&ids, start), 1651);
+        let tunnels = Tunnels::new(&parse(INPUT).unwrap());
+        assert_eq!(part_one(&tunnels, None), 1651);
     }
 
     #[test]
     fn example_part_two() {
-        let valves = parse(INPUT);
-        let compressed = compress(&valves);
-        let (ids, start) = identifiers(&compressed);
-        assert_eq!(part_two(&ids, start), 1707);
+        let tunnels = Tunnels::new(&parse(INPUT).unwrap());
+        assert_eq!(part_two(&tunnels, None), 1707);
+    }
+
+    #[test]
+    fn more_valves_than_a_word() {
+        // AA, then a line of 70 valves of flow 1
+        let name = |i: u8| match i {
+            0 => "AA".to_owned(),
+            _ => format!("{}{}", (b'B' + i / 26) as char, (b'A' + i % 26) as char),
+        };
+        let input = (0..=70u8)
+            .map(|i| {
+                let flow = if i == 0 { 0 } else { 1 };
+                let exits: Vec<String> = [i.checked_sub(1), (i < 70).then_some(i + 1)]
+                    .into_iter()
+                    .flatten()
+                    .map(name)
+                    .collect();
+                let tunnels = if exits.len() == 1 {
+                    "tunnel leads to valve"
+                } else {
+                    "tunnels lead to valves"
+                };
+                format!(
+                    "Valve {} has flow rate={}; {} {}",
+                    name(i),
+                    flow,
+                    tunnels,
+                    exits.join(", ")
+                )
+            })
+            .join("\n");
+
+        let tunnels = Tunnels::new(&parse(&input).unwrap());
+        assert_eq!(tunnels.valves.len(), 71);
+        // a valve opened every other minute, from 28 minutes left down to 2
+        assert_eq!(part_one(&tunnels, None), 210);
+    }
+
+    #[test]
+    fn heuristic_admissible() {
+        let tunnels = Tunnels::new(&parse(INPUT).unwrap());
+        // checking part two takes a search per state for its heuristic, far too slow for a test
+        let (_, reports) = instrumented(true, || part_one(&tunnels, None));
+        assert!(
+            matches!(reports[0].heuristic, Some(Admissibility::Admissible(_))),
+            "{}",
+            reports[0]
+        );
     }
 }
@@ -1,19 +1,64 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{
+        hash_map::{Entry, RandomState},
+        HashMap,
+    },
     fmt::Display,
+    hash::BuildHasher,
     str::from_utf8,
 };
 
-use crate::utils::{BitSet, Coord, LEFT, RIGHT};
+use crate::{
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    utils::{BitSet, Coord, FastState, LEFT, RIGHT},
+};
 
 const DOWN: Coord = Coord::new(0, -1);
 
-pub fn solution(input: &str) -> String {
-    let jets = parse(input);
-    format!("{}, {}", part_one(&jets), part_two(&jets))
+pub const PARAMS: &[Param] = &[
+    Param::new("rocks", 2022).at_least(0),
+    Param::new("rocks_two", 1_000_000_000_000).at_least(0),
+];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let jets = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&jets, params.get("rocks")),
+        part_two::<FastState>(&jets, params.get("rocks_two"))
+    ))
+}
+
+/// The solution with the chamber signatures hashed by the standard SipHash, to compare against.
+pub fn solution_siphash(input: &str, params: &Params) -> Result<String, SolveError> {
+    let jets = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&jets, params.get("rocks")),
+        part_two::<RandomState>(&jets, params.get("rocks_two"))
+    ))
+}
+
+/// The top of the chamber once part one's rocks have stopped.
+pub fn render_chamber(input: &str, params: &Params) -> Result<String, SolveError> {
+    let jets = parse(input)?;
+    Ok(drop_rocks(&jets, params.get("rocks")).to_string())
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
 }
 
-#[derive(Clone, Copy)]
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let jets = parse(input)?;
+    let printed = print(&jets);
+    printed_back(&jets, parse(&printed))?;
+    Ok(printed)
+}
+
+#[derive(Clone, Copy, PartialEq)]
 enum Jet {
     Left,
     Right,
@@ -28,41 +73,54 @@ impl Jet {
     }
 }
 
-fn parse(input: &str) -> Vec<Jet> {
+fn parse(input: &str) -> Result<Vec<Jet>, ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::new("empty jet pattern"));
+    }
+
     input
         .chars()
         .map(|c| match c {
-            '<' => Jet::Left,
-            '>' => Jet::Right,
-            _ => unreachable!("invalid jet pattern"),
+            '<' => Ok(Jet::Left),
+            '>' => Ok(Jet::Right),
+            _ => Err(ParseError::new(format!("jet pattern: {:?}", c))),
+        })
+        .collect()
+}
+
+fn print(jets: &[Jet]) -> String {
+    jets.iter()
+        .map(|jet| match jet {
+            Jet::Left => '<',
+            Jet::Right => '>',
         })
         .collect()
 }
 
-/// pieces are upside down
+// pieces are upside down, bit `i` of a row is `i` columns right of the piece's left edge
 
 /// ####
-const BAR: &[u64] = &[0b111100000];
+const BAR: &[u64] = &[0b1111];
 
 /// .#.
 /// ###
 /// .#.
-const CROSS: &[u64] = &[0b010000000, 0b111000000, 0b010000000];
+const CROSS: &[u64] = &[0b010, 0b111, 0b010];
 
 /// ..#
 /// ..#
 /// ###
-const CORNER: &[u64] = &[0b111000000, 0b001000000, 0b001000000];
+const CORNER: &[u64] = &[0b111, 0b100, 0b100];
 
 /// #
 /// #
 /// #
 /// #
-const PILLAR: &[u64] = &[0b100000000, 0b100000000, 0b100000000, 0b100000000];
+const PILLAR: &[u64] = &[0b1, 0b1, 0b1, 0b1];
 
 /// ##
 /// ##
-const SQUARE: &[u64] = &[0b110000000, 0b110000000];
+const SQUARE: &[u64] = &[0b11, 0b11];
 
 const ROCKS: [&[u64]; 5] = [BAR, CROSS, CORNER, PILLAR, SQUARE];
 
@@ -78,9 +136,14 @@ impl Piece {
     }
 
     fn rows(self) -> impl Iterator<Item = BitSet> {
-        self.rock
-            .iter()
-            .map(move |b| BitSet::from_bits(b >> self.bottom_left.x))
+        let left = self.bottom_left.x() as usize;
+        self.rock.iter().map(move |&b| {
+            let mut row = BitSet::new();
+            (0..u64::BITS as usize)
+                .filter(|i| b & (0b1 << i) != 0)
+                .for_each(|i| row.insert(left + i));
+            row
+        })
     }
 
     fn move_to(self, d: Coord) -> Self {
@@ -90,37 +153,48 @@ impl Piece {
 
 #[derive(Clone)]
 struct Chamber {
+    width: usize,
     stopped: Vec<BitSet>,
     highest: i64,
 }
 
 impl Chamber {
     const WIDTH: usize = 7;
-    const WALL: BitSet = BitSet::from_bits((0b1 << (Self::WIDTH + 1)) | 0b1);
-    const BOTTOM: BitSet = BitSet::from_bits((0b1 << (Self::WIDTH + 2)) - 1);
 
-    fn new() -> Self {
-        let stopped = vec![Self::BOTTOM];
+    fn new(width: usize) -> Self {
+        let mut bottom = BitSet::new();
+        (0..width + 2).for_each(|x| bottom.insert(x));
         Self {
-            stopped,
+            width,
+            stopped: vec![bottom],
             highest: 0,
         }
     }
 
+    /// The walls on both sides of an empty row, in columns 0 and `width + 1`.
+    fn wall(&self) -> BitSet {
+        let mut wall = BitSet::new();
+        wall.insert(0);
+        wall.insert(self.width + 1);
+        wall
+    }
+
     fn blocked(&self, piece: Piece) -> bool {
         piece.rows().enumerate().any(|(i, r)| {
-            let y = piece.bottom_left.y as usize + i;
-            let c = self.stopped.get(y).copied().unwrap_or(Self::WALL);
-            !r.intersection(&c).is_empty()
+            let y = piece.bottom_left.y() as usize + i;
+            match self.stopped.get(y) {
+                Some(c) => !r.intersection(c).is_empty(),
+                None => !r.intersection(&self.wall()).is_empty(),
+            }
         })
     }
 
     fn stop(&mut self, piece: Piece) {
         debug_assert!(!self.blocked(piece));
         for (i, r) in piece.rows().enumerate() {
-            let y = piece.bottom_left.y as usize + i;
+            let y = piece.bottom_left.y() as usize + i;
             while self.stopped.len() <= y {
-                self.stopped.push(Self::WALL);
+                self.stopped.push(self.wall());
             }
             self.stopped[y] = self.stopped[y].union(&r);
             self.highest = self.highest.max(y as i64);
@@ -139,10 +213,10 @@ impl Chamber {
 impl Display for Chamber {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for line in self.stopped.iter().rev().take(10) {
-            let mut row = [b'.'; Self::WIDTH + 2];
+            let mut row = vec![b'.'; self.width + 2];
             row[0] = b'|';
-            row[Self::WIDTH + 1] = b'|';
-            for (x, b) in row.iter_mut().enumerate().skip(1).take(Self::WIDTH) {
+            row[self.width + 1] = b'|';
+            for (x, b) in row.iter_mut().enumerate().skip(1).take(self.width) {
                 if line.contains(x) {
                     *b = b'#';
                 }
@@ -185,27 +259,31 @@ where
     }
 }
 
-fn part_one(jets: &[Jet]) -> i64 {
+fn drop_rocks(jets: &[Jet], rocks_count: i64) -> Chamber {
     let mut jets = jets.iter().cycle().copied();
     let mut rocks = ROCKS.iter().cycle().copied();
 
-    let mut chamber = Chamber::new();
+    let mut chamber = Chamber::new(Chamber::WIDTH);
 
-    for _ in 0..2022 {
+    for _ in 0..rocks_count {
         one_piece(&mut chamber, rocks.next().unwrap(), &mut jets);
     }
 
-    chamber.highest
+    chamber
 }
 
-fn pattern_search(jets: &[Jet]) -> (i64, i64) {
-    let mut chamber = Chamber::new();
+fn part_one(jets: &[Jet], rocks_count: i64) -> i64 {
+    drop_rocks(jets, rocks_count).highest
+}
+
+fn pattern_search<S: BuildHasher + Default>(jets: &[Jet]) -> (i64, i64) {
+    let mut chamber = Chamber::new(Chamber::WIDTH);
 
     let mut jets = jets.iter().copied().enumerate().cycle();
     let mut rocks = ROCKS.iter().copied().enumerate().cycle();
 
     const TRIAL_LEN: i64 = 10000;
-    let mut records: HashMap<(usize, usize, Vec<BitSet>), i64> = HashMap::new();
+    let mut records: HashMap<(usize, usize, Vec<BitSet>), i64, S> = HashMap::default();
 
     for i in 0..TRIAL_LEN {
         let (rock_idx, rock) = rocks.next().unwrap();
@@ -230,11 +308,13 @@ fn pattern_search(jets: &[Jet]) -> (i64, i64) {
     unreachable!("must be a pattern")
 }
 
-const PART_TWO_ROCKS: i64 = 1000000000000;
+fn part_two<S: BuildHasher + Default>(jets: &[Jet], rocks_count: i64) -> i64 {
+    let (skip, pattern_len) = pattern_search::<S>(jets);
+    if rocks_count <= skip {
+        return part_one(jets, rocks_count);
+    }
 
-fn part_two(jets: &[Jet]) -> i64 {
-    let (skip, pattern_len) = pattern_search(jets);
-    let mut chamber = Chamber::new();
+    let mut chamber = Chamber::new(Chamber::WIDTH);
 
     let mut jets = jets.iter().cycle().copied();
     let mut rocks = ROCKS.iter().cycle().copied();
@@ -253,8 +333,8 @@ fn part_two(jets: &[Jet]) -> i64 {
         chamber.highest - skip_height
     };
 
-    let pattern_height = (PART_TWO_ROCKS - skip) / pattern_len * loop_height_growth;
-    let remaining_rocks = (PART_TWO_ROCKS - skip) % pattern_len;
+    let pattern_height = (rocks_count - skip) / pattern_len * loop_height_growth;
+    let remaining_rocks = (rocks_count - skip) % pattern_len;
 
     for rock in rocks.take(remaining_rocks as usize) {
         one_piece(&mut skip_chamber, rock, &mut jets);
@@ -271,13 +351,28 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let jets = parse(INPUT);
-        assert_eq!(part_one(&jets), 3068);
+        let jets = parse(INPUT).unwrap();
+        assert_eq!(part_one(&jets, 2022), 3068);
     }
 
     #[test]
     fn example_part_two() {
-        let jets = parse(INPUT);
-        assert_eq!(part_two(&jets), 1514285714288);
+        let jets = parse(INPUT).unwrap();
+        assert_eq!(
+            part_two::<FastState>(&jets, 1_000_000_000_000),
+            1514285714288
+        );
+    }
+    #[test]
+    fn wide_chamber() {
+        let jets = parse(INPUT).unwrap();
+        let mut jets = jets.iter().cycle().copied();
+        let mut chamber = Chamber::new(100);
+        for rock in ROCKS.iter().cycle().take(20) {
+            one_piece(&mut chamber, rock, &mut jets);
+        }
+        assert!(chamber.highest > 0);
+        assert!(chamber.stopped.iter().all(|row| row.contains(101)));
+        assert!(chamber.to_string().lines().all(|l| l.len() == 102));
     }
 }
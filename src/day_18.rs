@@ -1,129 +1,88 @@
-use std::{collections::HashSet, ops::Add};
-
-use itertools::Itertools;
 use nom::{
-    bytes::complete::tag,
-    character::complete::{self, line_ending},
-    combinator::all_consuming,
-    multi::separated_list1,
-    IResult,
+    character::complete::line_ending, combinator::all_consuming, multi::separated_list1, IResult,
 };
 use pathfinding::prelude::dfs_reach;
 
-use crate::utils::Closed;
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::{p_point, Bounds, FastSet, Point, Rng},
+};
 
-pub fn solution(input: &str) -> String {
-    let coords = parse(input);
-    format!("{}, {}", part_one(&coords), part_two(&coords))
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let coords = parse(input)?;
+    Ok(format!("{}, {}", part_one(&coords), part_two(&coords)))
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct Coord3 {
-    x: i64,
-    y: i64,
-    z: i64,
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
 }
 
-impl Coord3 {
-    const fn new(x: i64, y: i64, z: i64) -> Self {
-        Self { x, y, z }
-    }
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let coords = parse(input)?;
+    let printed = print(&coords);
+    printed_back(&coords, parse(&printed))?;
+    Ok(printed)
 }
 
-impl Add for Coord3 {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
-    }
+/// `size` random cubes filling about half of a droplet.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let side = ((2 * size) as f64).cbrt().ceil() as i64;
+    (0..size.max(1))
+        .map(|_| {
+            let mut c = || rng.range(1, side);
+            format!("{},{},{}", c(), c(), c())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-const UP: Coord3 = Coord3::new(0, 1, 0);
-const DOWN: Coord3 = Coord3::new(0, -1, 0);
-const LEFT: Coord3 = Coord3::new(-1, 0, 0);
-const RIGHT: Coord3 = Coord3::new(1, 0, 0);
-const FRONT: Coord3 = Coord3::new(0, 0, -1);
-const BACK: Coord3 = Coord3::new(0, 0, 1);
-const DIRS: [Coord3; 6] = [UP, DOWN, LEFT, RIGHT, FRONT, BACK];
+type Coord3 = Point<3>;
 
-fn parse(input: &str) -> Vec<Coord3> {
-    let (_, coords) = all_consuming(p_coords)(input).expect("valid complete parse");
-    coords
+fn parse(input: &str) -> Result<Vec<Coord3>, ParseError> {
+    let (_, coords) = all_consuming(p_coords)(input)?;
+    Ok(coords)
 }
 
-fn p_coords(input: &str) -> IResult<&str, Vec<Coord3>> {
-    separated_list1(line_ending, p_coord)(input)
+fn print(coords: &[Coord3]) -> String {
+    coords
+        .iter()
+        .map(|c| {
+            let [x, y, z] = c.0;
+            format!("{},{},{}", x, y, z)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-fn p_coord(input: &str) -> IResult<&str, Coord3> {
-    let (input, x) = complete::i64(input)?;
-    let (input, _) = tag(",")(input)?;
-    let (input, y) = complete::i64(input)?;
-    let (input, _) = tag(",")(input)?;
-    let (input, z) = complete::i64(input)?;
-
-    Ok((input, Coord3::new(x, y, z)))
+fn p_coords(input: &str) -> IResult<&str, Vec<Coord3>> {
+    separated_list1(line_ending, p_point)(input)
 }
 
 fn part_one(coords: &[Coord3]) -> usize {
-    let droplet: HashSet<_> = coords.iter().collect();
+    let droplet: FastSet<_> = coords.iter().collect();
 
     droplet
         .iter()
-        .map(|c| {
-            DIRS.into_iter()
-                .filter(|d| !droplet.contains(&(**c + *d)))
-                .count()
-        })
+        .map(|c| c.axis_neighbors().filter(|n| !droplet.contains(n)).count())
         .sum()
 }
 
-fn bounding_and_one(coords: &[Coord3]) -> (Closed, Closed, Closed) {
-    let (min_x, max_x) = coords.iter().map(|c| c.x).minmax().into_option().unwrap();
-    let (min_y, max_y) = coords.iter().map(|c| c.y).minmax().into_option().unwrap();
-    let (min_z, max_z) = coords.iter().map(|c| c.z).minmax().into_option().unwrap();
-
-    (
-        Closed::new(min_x - 1, max_x + 1),
-        Closed::new(min_y - 1, max_y + 1),
-        Closed::new(min_z - 1, max_z + 1),
-    )
-}
-
 fn part_two(coords: &[Coord3]) -> usize {
-    let droplet: HashSet<_> = coords.iter().collect();
-    let (x_range, y_range, z_range) = bounding_and_one(coords);
-
-    let outside: HashSet<_> = dfs_reach(
-        Coord3::new(x_range.start, y_range.start, z_range.start),
-        |&c| {
-            let droplet = &droplet;
-            DIRS.into_iter().filter_map(move |d| {
-                let n = d + c;
-                if !droplet.contains(&n)
-                    && x_range.contains(n.x)
-                    && y_range.contains(n.y)
-                    && z_range.contains(n.z)
-                {
-                    Some(n)
-                } else {
-                    None
-                }
-            })
-        },
-    )
+    let droplet: FastSet<_> = coords.iter().collect();
+    let bounds = Bounds::of(coords.iter().copied()).unwrap().grown(1);
+
+    let outside: FastSet<_> = dfs_reach(bounds.min, |&c| {
+        let droplet = &droplet;
+        c.axis_neighbors()
+            .filter(move |n| !droplet.contains(n) && bounds.contains(*n))
+    })
     .collect();
 
     droplet
         .iter()
-        .map(|c| {
-            DIRS.into_iter()
-                .filter(|d| {
-                    let n = **c + *d;
-                    outside.contains(&n)
-                })
-                .count()
-        })
+        .map(|c| c.axis_neighbors().filter(|n| outside.contains(n)).count())
         .sum()
 }
 
@@ -147,13 +106,13 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let coords = parse(INPUT);
+        let coords = parse(INPUT).unwrap();
         assert_eq!(part_one(&coords), 64);
     }
 
     #[test]
     fn example_part_two() {
-        let coords = parse(INPUT);
+        let coords = parse(INPUT).unwrap();
         assert_eq!(part_two(&coords), 58);
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt::{self, Display};
+
 use nom::{
     bytes::complete::tag,
     character::complete::{i32, line_ending},
@@ -6,21 +8,96 @@ use nom::{
     sequence::{delimited, separated_pair},
     IResult, Parser,
 };
-use pathfinding::prelude::astar;
 
-pub fn solution(input: &str) -> String {
-    let blueprints = parse(input);
-    format!("{}, {}", part_one(&blueprints), part_two(&blueprints))
+use crate::{
+    checkpoint::{self, Checkpoint, Checkpoints, Snapshot},
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    search::astar,
+    utils::Rng,
+};
+
+pub const PARAMS: &[Param] = &[
+    Param::new("minutes", 24)
+        .at_least(0)
+        .at_most(i32::MAX as i64),
+    Param::new("minutes_two", 32)
+        .at_least(0)
+        .at_most(i32::MAX as i64),
+    Param::new("blueprints_two", 3).at_least(0),
+    checkpoint::PARAM,
+];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let blueprints = parse(input)?;
+    let checkpoints = Checkpoints::from_params(input, params);
+    Ok(format!(
+        "{}, {}",
+        part_one(
+            &blueprints,
+            params.get("minutes") as i32,
+            checkpoints.as_ref()
+        ),
+        part_two(
+            &blueprints,
+            params.get("minutes_two") as i32,
+            params.get("blueprints_two") as usize,
+            checkpoints.as_ref()
+        )
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let blueprints = parse(input)?;
+    let printed = print(&blueprints);
+    printed_back(&blueprints, parse(&printed))?;
+    Ok(printed)
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+/// `size` blueprints with robots costing as much as in the puzzle.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let blueprints: Vec<Blueprint> = (1..=size.max(1))
+        .map(|id| {
+            let ore = |rng: &mut Rng| rng.range(2, 4) as i32;
+            Blueprint {
+                id: id as i32,
+                ore_cost: Cost {
+                    ore: ore(rng),
+                    ..Default::default()
+                },
+                clay_cost: Cost {
+                    ore: ore(rng),
+                    ..Default::default()
+                },
+                obsidian_cost: Cost {
+                    ore: ore(rng),
+                    clay: rng.range(5, 20) as i32,
+                    ..Default::default()
+                },
+                geode_cost: Cost {
+                    ore: ore(rng),
+                    obsidian: rng.range(5, 20) as i32,
+                    ..Default::default()
+                },
+            }
+        })
+        .collect();
+    print(&blueprints)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 struct Cost {
     ore: i32,
     clay: i32,
     obsidian: i32,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 struct Blueprint {
     id: i32,
     ore_cost: Cost,
@@ -29,10 +106,33 @@ struct Blueprint {
     geode_cost: Cost,
 }
 
-fn parse(input: &str) -> Vec<Blueprint> {
-    let (_, blueprints) = all_consuming(separated_list1(line_ending, p_blueprint))(input)
-        .expect("valid complete parse");
-    blueprints
+impl Display for Blueprint {
+    /// The blueprint on a single line as in the puzzle input, not wrapped as in the example.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+             Each obsidian robot costs {} ore and {} clay. \
+             Each geode robot costs {} ore and {} obsidian.",
+            self.id,
+            self.ore_cost.ore,
+            self.clay_cost.ore,
+            self.obsidian_cost.ore,
+            self.obsidian_cost.clay,
+            self.geode_cost.ore,
+            self.geode_cost.obsidian
+        )
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    let (_, blueprints) = all_consuming(separated_list1(line_ending, p_blueprint))(input)?;
+    Ok(blueprints)
+}
+
+fn print(blueprints: &[Blueprint]) -> String {
+    let blueprints: Vec<String> = blueprints.iter().map(Blueprint::to_string).collect();
+    blueprints.join("\n")
 }
 
 fn p_blueprint(input: &str) -> IResult<&str, Blueprint> {
@@ -89,7 +189,7 @@ const CLAY: usize = 1;
 const OBSIDIAN: usize = 2;
 const GEODE: usize = 3;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct State {
     remaining: i32,
     /// [ore, clay, obsidian, geode]
@@ -184,56 +284,135 @@ impl State {
     }
 }
 
-fn part_one(blueprints: &[Blueprint]) -> i32 {
-    blueprints
-        .iter()
-        .map(|b| {
-            let (_, cost) = astar(
-                &State::new(24),
-                |state| state.moves(b),
-                |state| state.heuristic(b),
-                |state| state.done(),
-            )
-            .unwrap();
-
-            b.id * -cost
+impl Snapshot for State {
+    fn save(&self, fields: &mut Vec<i64>) {
+        fields.push(self.remaining as i64);
+        fields.extend(self.robots.map(i64::from));
+        fields.extend(self.resources.map(i64::from));
+    }
+
+    fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self> {
+        let mut next = || fields.next().map(|n| n as i32);
+        let remaining = next()?;
+        let mut robots = [0; 4];
+        for robot in &mut robots {
+            *robot = next()?;
+        }
+        let mut resources = [0; 4];
+        for resource in &mut resources {
+            *resource = next()?;
+        }
+        Some(Self {
+            remaining,
+            robots,
+            resources,
         })
-        .sum()
+    }
+}
+
+/// Searches of finished blueprints stay answered by their checkpoint until the whole part is done.
+/// They are told apart by their position in the input, as nothing keeps two blueprints from having
+/// the same id.
+fn checkpoint(checkpoints: Option<&Checkpoints>, index: usize, minutes: i32) -> Option<Checkpoint> {
+    checkpoints.map(|c| c.get(&format!("day_19_blueprint_{}_{}", index, minutes)))
 }
 
-fn part_two(blueprints: &[Blueprint]) -> i32 {
-    blueprints
+fn max_geodes(blueprint: &Blueprint, minutes: i32, checkpoint: Option<&mut Checkpoint>) -> i32 {
+    let cost = astar(
+        &State::new(minutes),
+        |state| {
+            state
+                .moves(blueprint)
+                .into_iter()
+                .map(|(s, c)| (s, c as i64))
+        },
+        |state| state.heuristic(blueprint) as i64,
+        |state| state.done(),
+        checkpoint,
+    )
+    .expect("the search ends when time runs out");
+
+    -cost as i32
+}
+
+fn part_one(blueprints: &[Blueprint], minutes: i32, checkpoints: Option<&Checkpoints>) -> i32 {
+    let mut searches: Vec<_> = blueprints
         .iter()
-        .take(3)
-        .map(|b| {
-            let (_, cost) = astar(
-                &State::new(32),
-                |state| state.moves(b),
-                |state| state.heuristic(b),
-                |state| state.done(),
-            )
-            .unwrap();
-            -cost
-        })
-        .product()
+        .enumerate()
+        .map(|(i, b)| (b, checkpoint(checkpoints, i, minutes)))
+        .collect();
+    let quality = searches
+        .iter_mut()
+        .map(|(b, c)| b.id * max_geodes(b, minutes, c.as_mut()))
+        .sum();
+    searches
+        .iter()
+        .flat_map(|(_, c)| c)
+        .for_each(Checkpoint::clear);
+
+    quality
+}
+
+fn part_two(
+    blueprints: &[Blueprint],
+    minutes: i32,
+    count: usize,
+    checkpoints: Option<&Checkpoints>,
+) -> i32 {
+    let mut searches: Vec<_> = blueprints
+        .iter()
+        .take(count)
+        .enumerate()
+        .map(|(i, b)| (b, checkpoint(checkpoints, i, minutes)))
+        .collect();
+    let product = searches
+        .iter_mut()
+        .map(|(b, c)| max_geodes(b, minutes, c.as_mut()))
+        .product();
+    searches
+        .iter()
+        .flat_map(|(_, c)| c)
+        .for_each(Checkpoint::clear);
+
+    product
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::{instrumented, Admissibility};
 
     const INPUT: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
 
     #[test]
     fn example_part_one() {
-        let blueprints = parse(INPUT);
-        assert_eq!(part_one(&blueprints), 33);
+        let blueprints = parse(INPUT).unwrap();
+        assert_eq!(part_one(&blueprints, 24, None), 33);
     }
 
     #[test]
     fn example_part_two() {
-        let blueprints = parse(INPUT);
-        assert_eq!(part_two(&blueprints), 56 * 62);
+        let blueprints = parse(INPUT).unwrap();
+        assert_eq!(part_two(&blueprints, 32, 3, None), 56 * 62);
+    }
+
+    #[test]
+    fn heuristic_admissible() {
+        let blueprints = parse(INPUT).unwrap();
+        // every state reachable in a full day is far too many to check
+        let (_, reports) = instrumented(true, || {
+            for blueprint in &blueprints {
+                max_geodes(blueprint, 15, None);
+            }
+        });
+        assert_eq!(reports.len(), 2);
+        for report in reports {
+            assert!(
+                matches!(report.heuristic, Some(Admissibility::Admissible(_))),
+                "{}",
+                report
+            );
+        }
     }
 }
@@ -1,6 +1,38 @@
-pub fn solution(input: &str) -> String {
-    let guide = parse(input);
-    format!("{}, {}", part_one(&guide), part_two(&guide))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let guide = parse(input)?;
+    Ok(format!("{}, {}", part_one(&guide), part_two(&guide)))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let guide = parse(input)?;
+    let printed = print(&guide);
+    printed_back(&guide, parse(&printed))?;
+    Ok(printed)
+}
+
+/// A strategy guide of `size` rounds.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}",
+                rng.pick(&['A', 'B', 'C']),
+                rng.pick(&['X', 'Y', 'Z'])
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -38,7 +70,7 @@ impl Shape {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 enum Response {
     X,
     Y,
@@ -89,29 +121,53 @@ impl Outcome {
     }
 }
 
-fn parse(input: &str) -> Vec<(Shape, Response)> {
+fn parse(input: &str) -> Result<Vec<(Shape, Response)>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let bytes = line.as_bytes();
-            let shape = match bytes[0] {
+            let invalid = || ParseError::new(format!("round: {:?}", line));
+            let &[s, b' ', r] = line.as_bytes() else {
+                return Err(invalid());
+            };
+
+            let shape = match s {
                 b'A' => Shape::Rock,
                 b'B' => Shape::Paper,
                 b'C' => Shape::Scissor,
-                _ => unreachable!(),
+                _ => return Err(invalid()),
             };
-            let response = match bytes[2] {
+            let response = match r {
                 b'X' => Response::X,
                 b'Y' => Response::Y,
                 b'Z' => Response::Z,
-                _ => unreachable!(),
+                _ => return Err(invalid()),
             };
 
-            (shape, response)
+            Ok((shape, response))
         })
         .collect()
 }
 
+fn print(guide: &[(Shape, Response)]) -> String {
+    guide
+        .iter()
+        .map(|(shape, response)| {
+            let shape = match shape {
+                Shape::Rock => 'A',
+                Shape::Paper => 'B',
+                Shape::Scissor => 'C',
+            };
+            let response = match response {
+                Response::X => 'X',
+                Response::Y => 'Y',
+                Response::Z => 'Z',
+            };
+            format!("{} {}", shape, response)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn part_one(guide: &[(Shape, Response)]) -> u32 {
     guide
         .iter()
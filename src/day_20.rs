@@ -1,14 +1,83 @@
-pub fn solution(input: &str) -> String {
-    let sequence = parse(input);
-    format!("{}, {}", part_one(&sequence), part_two(&sequence))
+use crate::{
+    input::{printed_back, ParseError},
+    overflow::{Arith, Overflow},
+    params::{Param, Params},
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub const PARAMS: &[Param] = &[
+    Param::new("decryption_key", DECRYPTION_KEY as i64),
+    Param::new("mixes", 10).at_least(0),
+];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let sequence = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&sequence)?,
+        part_two(
+            &sequence,
+            params.get("decryption_key") as isize,
+            params.get("mixes")
+        )?
+    ))
 }
 
-fn parse(input: &str) -> Vec<isize> {
-    input.lines().map(|l| l.parse().unwrap()).collect()
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let sequence = parse(input)?;
+    let printed = print(&sequence);
+    printed_back(&sequence, parse(&printed))?;
+    Ok(printed)
+}
+
+/// An encrypted file of `size` numbers with a single zero.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let mut numbers: Vec<i64> = (0..size.max(1))
+        .map(|_| rng.range(1, 10_000) * rng.pick(&[-1, 1]))
+        .collect();
+    let zero = rng.below(numbers.len() as u64) as usize;
+    numbers[zero] = 0;
+    numbers
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse(input: &str) -> Result<Vec<isize>, ParseError> {
+    let sequence: Vec<isize> = input
+        .lines()
+        .map(|l| {
+            l.parse()
+                .map_err(|_| ParseError::new(format!("number: {:?}", l)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    if sequence.contains(&0) {
+        Ok(sequence)
+    } else {
+        Err(ParseError::new("no 0 to count the grove coordinates from"))
+    }
+}
+
+fn print(sequence: &[isize]) -> String {
+    sequence
+        .iter()
+        .map(isize::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn shift(i: isize, len: usize) -> isize {
-    if i <= 0 {
+    if len == 1 {
+        // a lone number stays where it is
+        0
+    } else if i <= 0 {
         // +0 => +0
         // -1 => +5
         // -5 => +1
@@ -53,18 +122,18 @@ fn shuffle(sequence: &[isize], indices: &[isize]) -> Vec<isize> {
     shuffled
 }
 
-fn coordinates(shuffled: &[isize]) -> isize {
-    let z = shuffled.iter().position(|n| *n == 0).unwrap();
-    [1000, 2000, 3000]
-        .into_iter()
-        .map(|d| {
-            let after_idx = (z + d) % shuffled.len();
-            shuffled[after_idx]
-        })
-        .sum()
+fn coordinates(shuffled: &[isize]) -> Result<isize, Overflow> {
+    let z = shuffled
+        .iter()
+        .position(|n| *n == 0)
+        .expect("0 in the sequence");
+    Arith::new(20, "coordinates").sum([1000, 2000, 3000].into_iter().map(|d| {
+        let after_idx = (z + d) % shuffled.len();
+        shuffled[after_idx]
+    }))
 }
 
-fn part_one(sequence: &[isize]) -> isize {
+fn part_one(sequence: &[isize]) -> Result<isize, Overflow> {
     let mut indices: Vec<_> = (0..sequence.len()).map(|i| i as isize).collect();
     mix(sequence, &mut indices);
     coordinates(&shuffle(sequence, &indices))
@@ -72,14 +141,15 @@ fn part_one(sequence: &[isize]) -> isize {
 
 const DECRYPTION_KEY: isize = 811589153;
 
-fn part_two(sequence: &[isize]) -> isize {
+fn part_two(sequence: &[isize], key: isize, mixes: i64) -> Result<isize, Overflow> {
     let mut indices: Vec<_> = (0..sequence.len()).map(|i| i as isize).collect();
     let mut multiplied = sequence.to_vec();
+    let decrypt = Arith::new(20, "decryption key multiplication");
     for n in &mut multiplied {
-        *n *= DECRYPTION_KEY;
+        *n = decrypt.mul(*n, key)?;
     }
 
-    for _ in 0..10 {
+    for _ in 0..mixes {
         mix(&multiplied, &mut indices);
     }
 
@@ -98,15 +168,22 @@ mod tests {
 0
 4";
 
+    #[test]
+    fn lone_number_mixed() {
+        let sequence = parse("0").unwrap();
+        assert_eq!(part_one(&sequence), Ok(0));
+        assert_eq!(part_two(&sequence, DECRYPTION_KEY, 10), Ok(0));
+    }
+
     #[test]
     fn example_part_one() {
-        let sequence = parse(INPUT);
-        assert_eq!(part_one(&sequence), 3);
+        let sequence = parse(INPUT).unwrap();
+        assert_eq!(part_one(&sequence), Ok(3));
     }
 
     #[test]
     fn example_part_two_one_round() {
-        let sequence = parse(INPUT);
+        let sequence = parse(INPUT).unwrap();
         let mut indices: Vec<_> = (0..sequence.len()).map(|i| i as isize).collect();
         let mut multiplied = sequence.to_vec();
         for n in &mut multiplied {
@@ -136,7 +213,7 @@ mod tests {
 
     #[test]
     fn example_part_two() {
-        let sequence = parse(INPUT);
-        assert_eq!(part_two(&sequence), 1623178306);
+        let sequence = parse(INPUT).unwrap();
+        assert_eq!(part_two(&sequence, DECRYPTION_KEY, 10), Ok(1623178306));
     }
 }
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    rc::Rc,
+};
 
 use nom::{
     branch::alt,
@@ -11,12 +15,116 @@ use nom::{
     IResult, Parser,
 };
 
-pub fn solution(input: &str) -> String {
-    let yells = parse(input);
-    format!("{}, {}", part_one(&yells), part_two(&yells))
+use crate::{
+    input::{printed_back, ParseError},
+    overflow::{Arith, Overflow},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let yells = parse(input)?;
+    Ok(format!("{}, {}", part_one(&yells)?, part_two(&yells)?))
 }
 
-#[derive(Debug, Clone, Copy)]
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let yells = parse(input)?;
+    let printed = print(&yells);
+    printed_back(&yells, parse(&printed))?;
+    Ok(printed)
+}
+
+/// Monkeys doing `size` operations, at most 200,000, with root's two numbers equal once humn
+/// yells its own number. Every division is exact and no number grows much above a million.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    enum Node {
+        Op(BinOp, usize, usize),
+        Number(i64),
+        Humn(i64),
+    }
+
+    /// Adds a monkey yelling `value` after `ops` operations, humn among those it waits on if
+    /// `humn`, and returns its index.
+    fn monkey(value: i64, ops: usize, humn: bool, rng: &mut Rng, nodes: &mut Vec<Node>) -> usize {
+        let index = nodes.len();
+        nodes.push(if humn && ops == 0 {
+            Node::Humn(value)
+        } else {
+            Node::Number(value)
+        });
+        if ops == 0 {
+            return index;
+        }
+
+        // solving for humn divides by the other operand, never zero
+        let d = rng.range(1, 5);
+        let (op, lhs, rhs) = match rng.below(4) {
+            0 if value != 0 && value % d == 0 => (Mul, value / d, d),
+            1 if value != 0 && value.abs() < 1_000_000 => (Div, value * d, d),
+            2 => {
+                let rhs = rng.range(-1000, 1000);
+                (Sub, value + rhs, rhs)
+            }
+            _ => {
+                let lhs = rng.range(-1000, 1000);
+                (Add, lhs, value - lhs)
+            }
+        };
+        let humn_left = humn && (op == Div || rng.below(2) == 0);
+        let lhs_ops = rng.below(ops as u64) as usize;
+        let lhs = monkey(lhs, lhs_ops, humn_left, rng, nodes);
+        let rhs = monkey(rhs, ops - 1 - lhs_ops, humn && !humn_left, rng, nodes);
+        nodes[index] = Node::Op(op, lhs, rhs);
+        index
+    }
+
+    let ops = size.clamp(1, 200_000);
+    let (value, humn_left) = (rng.range(1, 100_000), rng.below(2) == 0);
+    let lhs_ops = rng.below(ops as u64) as usize;
+    let mut nodes = vec![Node::Number(value)];
+    let lhs = monkey(value, lhs_ops, humn_left, rng, &mut nodes);
+    let rhs = monkey(value, ops - 1 - lhs_ops, !humn_left, rng, &mut nodes);
+    nodes[0] = Node::Op(rng.pick(&[Add, Sub, Mul, Div]), lhs, rhs);
+
+    let mut names = (0..).map(|i: usize| -> String {
+        (0..4)
+            .rev()
+            .map(|p| char::from(b'a' + (i / 26usize.pow(p) % 26) as u8))
+            .collect()
+    });
+    let names: Vec<String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| match node {
+            _ if i == 0 => "root".to_owned(),
+            Node::Humn(_) => "humn".to_owned(),
+            _ => names.find(|n| n != "root" && n != "humn").unwrap(),
+        })
+        .collect();
+    let yells: Vec<Yell> = nodes
+        .iter()
+        .zip(&names)
+        .map(|(node, monkey)| Yell {
+            monkey,
+            job: match *node {
+                Node::Op(op, lhs, rhs) => Job::Expr(Expr {
+                    op,
+                    lhs: &names[lhs],
+                    rhs: &names[rhs],
+                }),
+                Node::Number(n) | Node::Humn(n) => Job::Number(n),
+            },
+        })
+        .collect();
+    print(&yells)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BinOp {
     Add,
     Sub,
@@ -25,31 +133,34 @@ enum BinOp {
 }
 
 impl BinOp {
-    fn apply(self, lhs: i64, rhs: i64) -> i64 {
+    fn apply(self, lhs: i64, rhs: i64) -> Result<i64, Overflow> {
+        let arith = Arith::new(21, "BinOp::apply");
         match self {
-            Add => lhs + rhs,
-            Sub => lhs - rhs,
-            Mul => lhs * rhs,
-            Div => lhs / rhs,
+            Add => arith.add(lhs, rhs),
+            Sub => arith.sub(lhs, rhs),
+            Mul => arith.mul(lhs, rhs),
+            Div => arith.div(lhs, rhs),
         }
     }
 
-    fn lhs_should_be(self, rhs: i64, result: i64) -> i64 {
+    fn lhs_should_be(self, rhs: i64, result: i64) -> Result<i64, Overflow> {
+        let arith = Arith::new(21, "BinOp::lhs_should_be");
         match self {
-            Add => result - rhs,
-            Sub => result + rhs,
-            Mul => result / rhs,
-            Div => result * rhs,
+            Add => arith.sub(result, rhs),
+            Sub => arith.add(result, rhs),
+            Mul => arith.div(result, rhs),
+            Div => arith.mul(result, rhs),
         }
     }
 
-    fn rhs_should_be(self, lhs: i64, result: i64) -> i64 {
+    fn rhs_should_be(self, lhs: i64, result: i64) -> Result<i64, Overflow> {
+        let arith = Arith::new(21, "BinOp::rhs_should_be");
         match self {
-            Add => result - lhs,
-            Sub => lhs - result,
-            Mul => result / lhs,
+            Add => arith.sub(result, lhs),
+            Sub => arith.sub(lhs, result),
+            Mul => arith.div(result, lhs),
             // could be problematic because of remainder
-            Div => lhs / result,
+            Div => arith.div(lhs, result),
         }
     }
 }
@@ -57,35 +168,101 @@ impl BinOp {
 use pathfinding::prelude::topological_sort;
 use BinOp::*;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Expr<'a> {
     op: BinOp,
     lhs: &'a str,
     rhs: &'a str,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Job<'a> {
     Expr(Expr<'a>),
     Number(i64),
 }
 
+#[derive(Debug, PartialEq, Eq)]
 struct Yell<'a> {
     monkey: &'a str,
     job: Job<'a>,
 }
 
-fn parse(input: &str) -> Vec<Yell> {
-    let (_, yells) =
-        all_consuming(separated_list1(line_ending, p_yell))(input).expect("valid complete parse");
-    yells
+impl Display for Yell<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.job {
+            Job::Expr(Expr { op, lhs, rhs }) => {
+                let op = match op {
+                    Add => '+',
+                    Sub => '-',
+                    Mul => '*',
+                    Div => '/',
+                };
+                write!(f, "{}: {} {} {}", self.monkey, lhs, op, rhs)
+            }
+            Job::Number(n) => write!(f, "{}: {}", self.monkey, n),
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Yell<'_>>, ParseError> {
+    let (_, yells) = all_consuming(separated_list1(line_ending, p_yell))(input)?;
+
+    let monkeys: HashSet<&str> = yells.iter().map(|y| y.monkey).collect();
+    if !monkeys.contains("root") {
+        return Err(ParseError::new("no root monkey"));
+    }
+
+    let unknown = yells
+        .iter()
+        .filter_map(|y| match y.job {
+            Job::Expr(expr) => Some([expr.lhs, expr.rhs]),
+            Job::Number(_) => None,
+        })
+        .flatten()
+        .find(|m| !monkeys.contains(m));
+    if let Some(monkey) = unknown {
+        return Err(ParseError::new(format!("unknown monkey {}", monkey)));
+    }
+
+    let deps: HashMap<&str, Job> = yells.iter().map(|y| (y.monkey, y.job)).collect();
+    if !matches!(deps["root"], Job::Expr(_)) {
+        return Err(ParseError::new("root monkey yells a number"));
+    }
+    if !matches!(deps.get("humn"), Some(Job::Number(_))) {
+        return Err(ParseError::new("no humn monkey yelling a number"));
+    }
+    let sorted = toposort(&deps).ok_or_else(|| ParseError::new("monkeys waiting on each other"))?;
+
+    // part two solves for humn backwards, one operation at a time from root
+    let mut paths: HashMap<&str, u64> = HashMap::from([("root", 1)]);
+    for &monkey in sorted.iter().rev() {
+        if let Job::Expr(expr) = deps[monkey] {
+            let n = paths.get(monkey).copied().unwrap_or(0);
+            for m in [expr.lhs, expr.rhs] {
+                let p = paths.entry(m).or_default();
+                *p = p.saturating_add(n);
+            }
+        }
+    }
+    if paths.get("humn") != Some(&1) {
+        return Err(ParseError::new(
+            "humn isn't on exactly one path of operations to root",
+        ));
+    }
+
+    Ok(yells)
+}
+
+fn print(yells: &[Yell]) -> String {
+    let yells: Vec<String> = yells.iter().map(Yell::to_string).collect();
+    yells.join("\n")
 }
 
 fn p_monkey(input: &str) -> IResult<&str, &str> {
     take_while_m_n(4, 4, |c: char| c.is_ascii_lowercase())(input)
 }
 
-fn p_expr(input: &str) -> IResult<&str, Expr> {
+fn p_expr(input: &str) -> IResult<&str, Expr<'_>> {
     let (input, lhs) = p_monkey(input)?;
     let (input, op_char) = delimited(space1, one_of("+-*/"), space1)(input)?;
     let (input, rhs) = p_monkey(input)?;
@@ -101,17 +278,18 @@ fn p_expr(input: &str) -> IResult<&str, Expr> {
     Ok((input, Expr { op, lhs, rhs }))
 }
 
-fn p_job(input: &str) -> IResult<&str, Job> {
+fn p_job(input: &str) -> IResult<&str, Job<'_>> {
     alt((p_expr.map(Job::Expr), i64.map(Job::Number)))(input)
 }
 
-fn p_yell(input: &str) -> IResult<&str, Yell> {
+fn p_yell(input: &str) -> IResult<&str, Yell<'_>> {
     let (input, monkey) = terminated(p_monkey, tag(": "))(input)?;
     let (input, job) = p_job(input)?;
     Ok((input, Yell { monkey, job }))
 }
 
-fn toposort<'a>(deps: &'a HashMap<&str, Job>) -> Vec<&'a str> {
+/// The monkeys root waits on, every one after those it waits on, `None` if some wait on each other.
+fn toposort<'a>(deps: &HashMap<&'a str, Job<'a>>) -> Option<Vec<&'a str>> {
     let mut sorted = topological_sort(&["root"], |monkey| {
         let mut successors = vec![];
         if let Job::Expr(expr) = deps[monkey] {
@@ -119,29 +297,29 @@ fn toposort<'a>(deps: &'a HashMap<&str, Job>) -> Vec<&'a str> {
         }
         successors
     })
-    .expect("no cycle");
+    .ok()?;
     sorted.reverse();
-    sorted
+    Some(sorted)
 }
 
-fn part_one(yells: &[Yell]) -> i64 {
+fn part_one(yells: &[Yell]) -> Result<i64, Overflow> {
     let deps: HashMap<&str, Job> = yells.iter().map(|y| (y.monkey, y.job)).collect();
     let mut values: HashMap<&str, i64> = HashMap::new();
-    let sorted = toposort(&deps);
+    let sorted = toposort(&deps).expect("rejected by parse");
 
     for monkey in sorted {
         let v = match deps[monkey] {
             Job::Expr(expr) => {
                 let lhs = values[expr.lhs];
                 let rhs = values[expr.rhs];
-                expr.op.apply(lhs, rhs)
+                expr.op.apply(lhs, rhs)?
             }
             Job::Number(n) => n,
         };
         values.insert(monkey, v);
     }
 
-    values["root"]
+    Ok(values["root"])
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -161,67 +339,47 @@ enum Nested {
 }
 
 impl Nested {
-    fn you_should_yell(&self) -> i64 {
+    /// Follows the operations from root down to you, the only operand not reduced to a number on
+    /// every level as parse checks there is a single path from root to humn.
+    fn you_should_yell(&self) -> Result<i64, Overflow> {
         let (mut curr, mut equal) = match self {
             Nested::Expr { lhs, rhs, .. } => match (lhs.as_ref(), rhs.as_ref()) {
-                (expr @ Nested::Expr { .. }, Nested::Value(Value::Number(n)))
-                | (Nested::Value(Value::Number(n)), expr @ Nested::Expr { .. }) => (expr, *n),
-                _ => panic!("should be rejected by hypothesis test"),
+                (other, Nested::Value(Value::Number(n)))
+                | (Nested::Value(Value::Number(n)), other) => (other, *n),
+                _ => unreachable!("you are on one side of root"),
             },
-            _ => panic!("should be rejected by hypothesis test"),
+            Nested::Value(_) => unreachable!("root is an operation"),
         };
 
-        while let Nested::Expr { op, lhs, rhs } = curr {
-            match (lhs.as_ref(), rhs.as_ref()) {
-                (expr @ Nested::Expr { .. }, Nested::Value(Value::Number(n))) => {
-                    equal = op.lhs_should_be(*n, equal);
-                    curr = expr;
-                }
-                (Nested::Value(Value::Number(n)), expr @ Nested::Expr { .. }) => {
-                    equal = op.rhs_should_be(*n, equal);
-                    curr = expr;
-                }
-                (Nested::Value(Value::Number(n)), Nested::Value(Value::You)) => {
-                    return op.rhs_should_be(*n, equal);
-                }
-                (Nested::Value(Value::You), Nested::Value(Value::Number(n))) => {
-                    return op.lhs_should_be(*n, equal);
-                }
-                _ => panic!("should be rejected by hypothesis test"),
+        loop {
+            match curr {
+                Nested::Value(Value::You) => return Ok(equal),
+                Nested::Value(Value::Number(_)) => unreachable!("you are on one side of root"),
+                Nested::Expr { op, lhs, rhs } => match (lhs.as_ref(), rhs.as_ref()) {
+                    (expr, Nested::Value(Value::Number(n))) => {
+                        equal = op.lhs_should_be(*n, equal)?;
+                        curr = expr;
+                    }
+                    (Nested::Value(Value::Number(n)), expr) => {
+                        equal = op.rhs_should_be(*n, equal)?;
+                        curr = expr;
+                    }
+                    _ => unreachable!("you are on one side of every operation"),
+                },
             }
         }
-
-        unreachable!("should terminate in the loop")
-    }
-}
-
-/// On every level of the final nested expression of monkey "root", either lhs or rhs can be reduced
-/// to a number.
-fn hypothesis(nested: &Nested) -> bool {
-    let mut curr = nested;
-
-    while let Nested::Expr { lhs, rhs, .. } = curr {
-        match (lhs.as_ref(), rhs.as_ref()) {
-            (Nested::Value(Value::Number(..)), expr @ Nested::Expr { .. })
-            | (expr @ Nested::Expr { .. }, Nested::Value(Value::Number(..))) => curr = expr,
-            (Nested::Value(Value::Number(..)), Nested::Value(..))
-            | (Nested::Value(..), Nested::Value(Value::Number(..))) => return true,
-            _ => return false,
-        }
     }
-
-    unreachable!("should terminate in the loop")
 }
 
-fn part_two(yells: &[Yell]) -> i64 {
+fn part_two(yells: &[Yell]) -> Result<i64, Overflow> {
     let deps: HashMap<&str, Job> = yells.iter().map(|y| (y.monkey, y.job)).collect();
     let mut values: HashMap<&str, Rc<Nested>> = HashMap::new();
 
-    for monkey in toposort(&deps) {
+    for monkey in toposort(&deps).expect("rejected by parse") {
         let v = match &deps[monkey] {
             Job::Expr(expr) => match (&*values[expr.lhs], &*values[expr.rhs]) {
                 (Nested::Value(Value::Number(lhs)), Nested::Value(Value::Number(rhs))) => {
-                    Nested::Value(Value::Number(expr.op.apply(*lhs, *rhs)))
+                    Nested::Value(Value::Number(expr.op.apply(*lhs, *rhs)?))
                 }
                 _ => Nested::Expr {
                     op: expr.op,
@@ -241,7 +399,6 @@ fn part_two(yells: &[Yell]) -> i64 {
         values.insert(monkey, Rc::new(v));
     }
 
-    assert!(hypothesis(&values["root"]));
     values["root"].you_should_yell()
 }
 
@@ -267,13 +424,40 @@ hmdt: 32";
 
     #[test]
     fn example_part_one() {
-        let yells = parse(INPUT);
-        assert_eq!(part_one(&yells), 152);
+        let yells = parse(INPUT).unwrap();
+        assert_eq!(part_one(&yells), Ok(152));
     }
 
     #[test]
     fn example_part_two() {
-        let yells = parse(INPUT);
-        assert_eq!(part_two(&yells), 301);
+        let yells = parse(INPUT).unwrap();
+        assert_eq!(part_two(&yells), Ok(301));
+    }
+
+    #[test]
+    fn unsolvable_monkeys_rejected() {
+        for input in [
+            "root: humn + aaaa\naaaa: root - humn\nhumn: 1",
+            "root: aaaa + aaaa\naaaa: 1",
+            "root: 5\nhumn: 1",
+            "root: humn + aaaa\nhumn: aaaa * aaaa\naaaa: 1",
+            "root: humn + aaaa\naaaa: humn * bbbb\nhumn: 1\nbbbb: 2",
+        ] {
+            assert!(parse(input).is_err(), "{}", input);
+        }
+
+        let yells = parse("root: humn + aaaa\nhumn: 1\naaaa: 3").unwrap();
+        assert_eq!(part_two(&yells), Ok(3));
+    }
+
+    #[test]
+    fn generated_monkeys_solved() {
+        for size in [1, 10, 1000] {
+            let input = generate(size, &mut Rng::new(size as u64));
+            let yells = parse(&input).unwrap();
+            let humn = yells.iter().find(|y| y.monkey == "humn").unwrap();
+            assert!(part_one(&yells).is_ok());
+            assert_eq!(part_two(&yells).map(Job::Number), Ok(humn.job));
+        }
     }
 }
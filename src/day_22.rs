@@ -9,16 +9,68 @@ use nom::{
     IResult, Parser,
 };
 
-pub fn solution(input: &str) -> String {
-    let (map, path) = parse(input);
-    format!(
+/// Selects the hard-coded cube net: faces of real inputs are 50 wide, those of the example 4.
+pub const PARAMS: &[Param] = &[Param::new("side_len", SIDE_LEN)];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let (map, path) = parse(input)?;
+    let connected = match params.get("side_len") {
+        SIDE_LEN => CONNECTED_SIDES,
+        example::SIDE_LEN => example::CONNECTED_SIDES,
+        len => return Err(ParseError::new(format!("no cube net of side length {}", len)).into()),
+    };
+    if !map.folds(connected) {
+        return Err(ParseError::new(format!(
+            "map doesn't fold into the cube of side length {}",
+            params.get("side_len")
+        ))
+        .into());
+    }
+    Ok(format!(
         "{}, {}",
         part_one(&map, &path),
-        part_two(&map, &path, CONNECTED_SIDES)
-    )
+        part_two(&map, &path, connected)
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let (map, path) = parse(input)?;
+    let printed = print(&map, &path);
+    printed_back(&(map, path), parse(&printed))?;
+    Ok(printed)
+}
+
+/// The cube net of real inputs with walls scattered over it, followed by a path of `size` moves.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    const NET: [&[u8]; 4] = [b" ##", b" # ", b"## ", b"#  "];
+
+    let side = SIDE_LEN as usize;
+    let rows = (0..NET.len() * side)
+        .map(|y| {
+            (0..NET[0].len() * side)
+                .map(|x| match NET[y / side][x / side] {
+                    b' ' => Void,
+                    // keep the start open
+                    _ if y == 0 || rng.below(10) > 0 => Open,
+                    _ => Wall,
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut path = vec![Step::Forward(rng.range(1, SIDE_LEN))];
+    for _ in 1..size.max(1) {
+        path.push(Step::Turn(rng.pick(&[L, R])));
+        path.push(Step::Forward(rng.range(1, SIDE_LEN)));
+    }
+    print(&Map::new(rows), &path)
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Tile {
     Open,
     Wall,
@@ -26,24 +78,50 @@ enum Tile {
 }
 use Tile::*;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Turn {
     L,
     R,
 }
 use Turn::*;
 
-use crate::utils::{Coord, DOWN, LEFT, RIGHT, UP};
+use std::fmt::{self, Display};
 
-#[derive(Clone, Copy)]
+use crate::{
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    utils::{Coord, Grid, Rng, DOWN, LEFT, RIGHT, UP},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Step {
     Forward(i64),
     Turn(Turn),
 }
 
-fn parse(input: &str) -> (Map, Vec<Step>) {
-    let (_, (tiles, path)) = all_consuming(p_input)(input).expect("valid complete parse");
-    (Map { tiles }, path)
+impl Display for Step {
+    /// Steps are printed back to back in the path, two forward steps in a row would merge.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Forward(n) => write!(f, "{}", n),
+            Step::Turn(L) => f.write_str("L"),
+            Step::Turn(R) => f.write_str("R"),
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<(Map, Vec<Step>), ParseError> {
+    let (_, (tiles, path)) = all_consuming(p_input)(input)?;
+    if !tiles[0].contains(&Open) {
+        return Err(ParseError::new("no open tile on the top row"));
+    }
+    Ok((Map::new(tiles), path))
+}
+
+fn print(map: &Map, path: &[Step]) -> String {
+    let path: String = path.iter().map(Step::to_string).collect();
+    format!("{}\n\n{}", map, path)
 }
 
 fn p_input(input: &str) -> IResult<&str, (Vec<Vec<Tile>>, Vec<Step>)> {
@@ -81,13 +159,48 @@ fn p_step(input: &str) -> IResult<&str, Step> {
     ))(input)
 }
 
+#[derive(Debug, PartialEq)]
 struct Map {
-    tiles: Vec<Vec<Tile>>,
+    tiles: Grid<Tile>,
+}
+
+impl Display for Map {
+    /// Rows stop at their last tile like in the puzzle, without the void padding them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.tiles.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let row: String = row
+                .iter()
+                .map(|t| match t {
+                    Void => ' ',
+                    Open => '.',
+                    Wall => '#',
+                })
+                .collect();
+            f.write_str(row.trim_end())?;
+        }
+        Ok(())
+    }
 }
 
 impl Map {
+    /// The map of rows padded with void to the longest one.
+    fn new(mut rows: Vec<Vec<Tile>>) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize(width, Void);
+        }
+        Self {
+            tiles: Grid::from_rows(rows).expect("non-empty map"),
+        }
+    }
+
     fn start(&self) -> Actor {
-        let x = self.tiles[0]
+        let x = self
+            .tiles
+            .row(0)
             .iter()
             .position(|t| *t == Open)
             .expect("valid start condition");
@@ -99,11 +212,25 @@ impl Map {
     }
 
     fn get(&self, coord: Coord) -> Tile {
+        self.tiles.get(coord).copied().unwrap_or(Void)
+    }
+
+    /// Whether every step off the edge of the map is wrapped onto a tile by the cube net.
+    fn folds(&self, connected: &[Connected]) -> bool {
         self.tiles
-            .get(coord.y as usize)
-            .and_then(|row| row.get(coord.x as usize))
-            .copied()
-            .unwrap_or(Void)
+            .coords()
+            .filter(|&coord| self.get(coord) != Void)
+            .all(|coord| {
+                [Up, Left, Down, Right].into_iter().all(|facing| {
+                    if self.get(coord + facing.dir()) != Void {
+                        return true;
+                    }
+                    connected
+                        .iter()
+                        .find_map(|conn| conn.wrap(&Actor { coord, facing }))
+                        .is_some_and(|wrapped| self.get(wrapped.coord) != Void)
+                })
+            })
     }
 
     fn wrap(&self, mut coord: Coord, facing: Facing) -> Coord {
@@ -210,7 +337,7 @@ impl Actor {
                     let wrapped = connected
                         .iter()
                         .find_map(|conn| conn.wrap(self))
-                        .expect("cube is connected");
+                        .expect("map folds into the cube");
 
                     match map.get(wrapped.coord) {
                         Open => *self = wrapped,
@@ -223,7 +350,7 @@ impl Actor {
     }
 
     fn password(self) -> i64 {
-        1000 * (self.coord.y + 1) + 4 * (self.coord.x + 1) + self.facing.number()
+        1000 * (self.coord.y() + 1) + 4 * (self.coord.x() + 1) + self.facing.number()
     }
 }
 
@@ -268,22 +395,22 @@ impl Side {
     }
 
     fn is_horizontal(&self) -> bool {
-        self.from.y == self.to.y
+        self.from.y() == self.to.y()
     }
 
     fn len(&self) -> i64 {
         if self.is_horizontal() {
-            self.to.x - self.from.x + 1
+            self.to.x() - self.from.x() + 1
         } else {
-            self.to.y - self.from.y + 1
+            self.to.y() - self.from.y() + 1
         }
     }
 
     fn contains(&self, coord: Coord) -> bool {
         if self.is_horizontal() {
-            self.from.y == coord.y && self.from.x <= coord.x && self.to.x >= coord.x
+            self.from.y() == coord.y() && self.from.x() <= coord.x() && self.to.x() >= coord.x()
         } else {
-            self.from.x == coord.x && self.from.y <= coord.y && self.to.y >= coord.y
+            self.from.x() == coord.x() && self.from.y() <= coord.y() && self.to.y() >= coord.y()
         }
     }
 
@@ -326,9 +453,9 @@ impl Connected {
         };
 
         let d = if wrap_from.is_horizontal() {
-            actor.coord.x - wrap_from.from.x
+            actor.coord.x() - wrap_from.from.x()
         } else {
-            actor.coord.y - wrap_from.from.y
+            actor.coord.y() - wrap_from.from.y()
         };
 
         let d = if self.forward {
@@ -338,9 +465,9 @@ impl Connected {
         };
 
         let coord = if wrap_to.is_horizontal() {
-            Coord::new(wrap_to.from.x + d, wrap_to.from.y)
+            Coord::new(wrap_to.from.x() + d, wrap_to.from.y())
         } else {
-            Coord::new(wrap_to.from.x, wrap_to.from.y + d)
+            Coord::new(wrap_to.from.x(), wrap_to.from.y() + d)
         };
 
         Some(Actor {
@@ -391,29 +518,14 @@ const CONNECTED_SIDES: &[Connected] = &[
     ),
 ];
 
-#[cfg(test)]
-mod tests {
+/// The cube net of the example, unlike the one shared by real inputs.
+mod example {
     use super::*;
 
-    const INPUT: &str = "        ...#
-        .#..
-        #...
-        ....
-...#.......#
-........#...
-..#....#....
-..........#.
-        ...#....
-        .....#..
-        .#......
-        ......#.
-
-10R5L5R10L4R5L5";
-
-    const SIDE_LEN: i64 = 4;
+    pub const SIDE_LEN: i64 = 4;
 
     #[allow(clippy::identity_op)]
-    const EXAMPLE_CONNECTED_SIDES: &[Connected] = &[
+    pub const CONNECTED_SIDES: &[Connected] = &[
         // 3b
         Connected::new_backward(
             Side::new_horizontal(0, 2 * SIDE_LEN, SIDE_LEN, Down),
@@ -450,16 +562,36 @@ mod tests {
             Side::new_vertical(2 * SIDE_LEN, 2 * SIDE_LEN, SIDE_LEN, Right),
         ),
     ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5";
 
     #[test]
     fn example_part_one() {
-        let (map, path) = parse(INPUT);
+        let (map, path) = parse(INPUT).unwrap();
         assert_eq!(part_one(&map, &path), 6032);
     }
 
     #[test]
     fn example_part_two() {
-        let (map, path) = parse(INPUT);
-        assert_eq!(part_two(&map, &path, EXAMPLE_CONNECTED_SIDES), 5031);
+        let (map, path) = parse(INPUT).unwrap();
+        assert_eq!(part_two(&map, &path, example::CONNECTED_SIDES), 5031);
     }
 }
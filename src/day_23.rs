@@ -1,17 +1,58 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::RandomState, HashMap},
     fmt::{Display, Write},
+    hash::BuildHasher,
     str::from_utf8,
 };
 
-use crate::utils::{Closed, Coord, DOWN, LEFT, RIGHT, UP};
+use crate::{
+    checkpoint::{self, Checkpoint, Checkpoints, Snapshot},
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    utils::{Bounds, Coord, FastMap, FastState, DOWN, LEFT, RIGHT, UP},
+};
+
+pub const PARAMS: &[Param] = &[checkpoint::PARAM];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let groves = parse(input)?.grove;
+    let checkpoints = Checkpoints::from_params(input, params);
+    Ok(format!(
+        "{}, {}",
+        part_one(&groves),
+        part_two(&groves, checkpoints.as_ref())
+    ))
+}
 
-pub fn solution(input: &str) -> String {
-    let groves = parse(input);
-    format!("{}, {}", part_one(&groves), part_two(&groves))
+/// The solution with the elves hashed by the standard SipHash, to compare against.
+pub fn solution_siphash(input: &str, params: &Params) -> Result<String, SolveError> {
+    let groves: Grove<RandomState> = parse(input)?.grove.rehashed();
+    let checkpoints = Checkpoints::from_params(input, params);
+    Ok(format!(
+        "{}, {}",
+        part_one(&groves),
+        part_two(&groves, checkpoints.as_ref())
+    ))
 }
 
-#[derive(Default, Clone, Copy)]
+/// The grove after the ten rounds of part one.
+pub fn render_grove(input: &str, _params: &Params) -> Result<String, SolveError> {
+    Ok(after_rounds(&parse(input)?.grove, 10).to_string())
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let scan = parse(input)?;
+    let printed = print(&scan);
+    printed_back(&scan, parse(&printed))?;
+    Ok(printed)
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
 struct Elf {
     first_direction: usize,
 }
@@ -54,27 +95,24 @@ impl Propose {
 
 const PROPOSES: [Propose; 4] = [N, S, W, E];
 
-const SURROUNDING: [Coord; 8] = [
-    Coord::new(-1, -1),
-    Coord::new(-1, 0),
-    Coord::new(-1, 1),
-    Coord::new(0, -1),
-    Coord::new(0, 1),
-    Coord::new(1, -1),
-    Coord::new(1, 0),
-    Coord::new(1, 1),
-];
-
 #[derive(Clone)]
-struct Grove {
-    elves: HashMap<Coord, Elf>,
+struct Grove<S = FastState> {
+    elves: HashMap<Coord, Elf, S>,
 }
 
-impl Grove {
+impl<S: BuildHasher + Default> Grove<S> {
+    fn rehashed<T: BuildHasher + Default>(&self) -> Grove<T> {
+        Grove {
+            elves: self
+                .elves
+                .iter()
+                .map(|(&coord, &elf)| (coord, elf))
+                .collect(),
+        }
+    }
+
     fn adjacent_to_one(&self, coord: Coord) -> bool {
-        SURROUNDING
-            .into_iter()
-            .any(|d| self.elves.contains_key(&(coord + d)))
+        coord.all_neighbors().any(|n| self.elves.contains_key(&n))
     }
 
     fn destination(&self, coord: Coord, elf: Elf) -> Option<Coord> {
@@ -93,34 +131,43 @@ impl Grove {
             .map(|p| p.dir() + coord)
     }
 
-    fn bounding(&self) -> (Closed, Closed) {
-        let (min_x, max_x) = self
-            .elves
-            .keys()
-            .map(|c| c.x)
-            .minmax()
-            .into_option()
-            .unwrap();
-        let (min_y, max_y) = self
-            .elves
-            .keys()
-            .map(|c| c.y)
-            .minmax()
-            .into_option()
-            .unwrap();
+    fn bounding(&self) -> Bounds<2> {
+        Bounds::of(self.elves.keys().copied()).unwrap()
+    }
+}
 
-        (Closed::new(min_x, max_x), Closed::new(min_y, max_y))
+impl<S: BuildHasher> PartialEq for Grove<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elves == other.elves
     }
 }
 
-impl Display for Grove {
+impl<S: BuildHasher + Default> Snapshot for Grove<S> {
+    fn save(&self, fields: &mut Vec<i64>) {
+        for (coord, elf) in &self.elves {
+            fields.extend([coord.x(), coord.y(), elf.first_direction as i64]);
+        }
+    }
+
+    fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self> {
+        let mut elves = HashMap::default();
+        while let Some(x) = fields.next() {
+            let (y, first_direction) = (fields.next()?, fields.next()? as usize);
+            elves.insert(Coord::new(x, y), Elf { first_direction });
+        }
+        (!elves.is_empty()).then_some(Self { elves })
+    }
+}
+
+impl<S: BuildHasher + Default> Display for Grove<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (x_range, y_range) = self.bounding();
+        let bounds = self.bounding();
+        let size = bounds.size();
 
-        let mut buffer = vec![vec![b'.'; x_range.len() as usize]; y_range.len() as usize];
-        for coord in self.elves.keys() {
-            let x = (coord.x - x_range.start) as usize;
-            let y = (coord.y - y_range.start) as usize;
+        let mut buffer = vec![vec![b'.'; size.x() as usize]; size.y() as usize];
+        for &coord in self.elves.keys() {
+            let (x, y) = (coord - bounds.min).to_tuple();
+            let (x, y) = (x as usize, y as usize);
 
             buffer[y][x] = b'#';
         }
@@ -134,25 +181,57 @@ impl Display for Grove {
     }
 }
 
-fn parse(input: &str) -> Grove {
-    let mut elves = HashMap::new();
+/// The grove as scanned, with the empty ground the scan shows around the elves.
+#[derive(PartialEq)]
+struct Scan {
+    grove: Grove,
+    size: Coord,
+}
+
+fn parse(input: &str) -> Result<Scan, ParseError> {
+    let mut elves = FastMap::default();
+    let mut size = Coord::new(0, 0);
 
     for (r, l) in input.lines().enumerate() {
+        size = Coord::new(size.x().max(l.len() as i64), r as i64 + 1);
         for (c, b) in l.bytes().enumerate() {
-            if b == b'#' {
-                let coord = Coord::new(c as i64, r as i64);
-                elves.insert(coord, Elf::new());
+            match b {
+                b'#' => {
+                    let coord = Coord::new(c as i64, r as i64);
+                    elves.insert(coord, Elf::new());
+                }
+                b'.' => (),
+                _ => return Err(ParseError::new(format!("grove: {:?}", l))),
             }
         }
     }
 
-    Grove { elves }
+    if elves.is_empty() {
+        return Err(ParseError::new("no elf in the grove"));
+    }
+
+    Ok(Scan {
+        grove: Grove { elves },
+        size,
+    })
+}
+
+fn print(scan: &Scan) -> String {
+    let (width, height) = scan.size.to_tuple();
+
+    let mut rows = vec![vec![b'.'; width as usize]; height as usize];
+    for &coord in scan.grove.elves.keys() {
+        let (x, y) = coord.to_tuple();
+        rows[y as usize][x as usize] = b'#';
+    }
+
+    rows.iter().map(|row| from_utf8(row).unwrap()).join("\n")
 }
 
-fn round(grove: &mut Grove) -> bool {
+fn round<S: BuildHasher + Default>(grove: &mut Grove<S>) -> bool {
     let mut moved = false;
 
-    let mut proposed = HashMap::new();
+    let mut proposed: HashMap<Coord, Coord, S> = HashMap::default();
     for (&coord, &elf) in grove.elves.iter() {
         let dest = if !grove.adjacent_to_one(coord) {
             coord
@@ -165,7 +244,7 @@ fn round(grove: &mut Grove) -> bool {
         proposed.insert(coord, dest);
     }
 
-    let mut contention: HashMap<Coord, i64> = HashMap::new();
+    let mut contention: HashMap<Coord, i64, S> = HashMap::default();
     for &dest in proposed.values() {
         *contention.entry(dest).or_default() += 1;
     }
@@ -189,28 +268,55 @@ fn round(grove: &mut Grove) -> bool {
     moved
 }
 
-fn part_one(grove: &Grove) -> i64 {
+fn after_rounds<S: BuildHasher + Default + Clone>(grove: &Grove<S>, rounds: usize) -> Grove<S> {
     let mut grove = grove.clone();
-
-    for _ in 0..10 {
+    for _ in 0..rounds {
         round(&mut grove);
     }
+    grove
+}
 
-    let (x_range, y_range) = grove.bounding();
-    x_range.len() * y_range.len() - grove.elves.len() as i64
+fn part_one<S: BuildHasher + Default + Clone>(grove: &Grove<S>) -> i64 {
+    let grove = after_rounds(grove, 10);
+    let size = grove.bounding().size();
+    size.x() * size.y() - grove.elves.len() as i64
 }
 
-fn part_two(grove: &Grove) -> i64 {
-    let mut grove = grove.clone();
-    let mut r = 0;
+fn save_progress<S: BuildHasher + Default>(checkpoint: &mut Checkpoint, r: i64, grove: &Grove<S>) {
+    let mut fields = vec![r];
+    grove.save(&mut fields);
+    if let Err(e) = checkpoint.save(&(fields.iter().join(" ") + "\n")) {
+        eprintln!("checkpoint not saved: {}", e);
+    }
+}
+
+fn load_progress<S: BuildHasher + Default>(checkpoint: &Checkpoint) -> Option<(i64, Grove<S>)> {
+    let body = checkpoint.load()?;
+    let mut fields = body.split_whitespace().map_while(|n| n.parse().ok());
+    Some((fields.next()?, Grove::restore(&mut fields)?))
+}
+
+fn part_two<S: BuildHasher + Default + Clone>(
+    grove: &Grove<S>,
+    checkpoints: Option<&Checkpoints>,
+) -> i64 {
+    let mut checkpoint = checkpoints.map(|c| c.get("day_23_part_two"));
+    let (mut r, mut grove) = checkpoint
+        .as_ref()
+        .and_then(load_progress)
+        .unwrap_or_else(|| (0, grove.clone()));
 
     loop {
         let moved = round(&mut grove);
         r += 1;
 
         if !moved {
+            checkpoint.iter().for_each(Checkpoint::clear);
             return r;
         }
+        if let Some(checkpoint) = checkpoint.as_mut().filter(|c| c.due()) {
+            save_progress(checkpoint, r, &grove);
+        }
     }
 }
 
@@ -233,13 +339,37 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let grove = parse(INPUT);
+        let grove = parse(INPUT).unwrap().grove;
         assert_eq!(part_one(&grove), 110);
     }
 
     #[test]
     fn example_part_two() {
-        let grove = parse(INPUT);
-        assert_eq!(part_two(&grove), 20);
+        let grove = parse(INPUT).unwrap().grove;
+        assert_eq!(part_two(&grove, None), 20);
+    }
+
+    #[test]
+    fn margins_printed_back() {
+        assert_eq!(reprint(INPUT).unwrap(), INPUT);
+        assert_eq!(reprint("...\n.#.").unwrap(), "...\n.#.");
+    }
+
+    #[test]
+    fn resumed_part_two() {
+        let dir = std::env::temp_dir().join(format!("advent-day-23-{}", std::process::id()));
+        let checkpoints = Checkpoints::new(&dir, INPUT, std::time::Duration::ZERO);
+
+        let mut grove = parse(INPUT).unwrap().grove;
+        for _ in 0..12 {
+            round(&mut grove);
+        }
+        save_progress(&mut checkpoints.get("day_23_part_two"), 12, &grove);
+
+        // nothing but the saved progress could tell the example's answer from a lone elf
+        let lone = parse("#").unwrap().grove;
+        assert_eq!(part_two(&lone, Some(&checkpoints)), 20);
+        assert_eq!(part_two(&lone, Some(&checkpoints)), 1);
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
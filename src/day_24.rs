@@ -1,3 +1,5 @@
+use std::fmt::{self, Display};
+
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
@@ -7,16 +9,75 @@ use nom::{
     sequence::{delimited, tuple},
     IResult, Parser,
 };
-use pathfinding::prelude::astar;
+use pathfinding::prelude::dijkstra;
+
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    search::astar_plain,
+    timing::{phase, PART_ONE, PART_TWO, PREPROCESS},
+    utils::{Coord, Grid, Rng, DOWN, LEFT, RIGHT, STAY, UP},
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let valley = parse(input)?;
+    let map = phase(PREPROCESS, || Map::new(&valley));
+    Ok(format!(
+        "{}, {}",
+        phase(PART_ONE, || part_one(&map))?,
+        phase(PART_TWO, || part_two(&map))?
+    ))
+}
 
-use crate::utils::{Coord, DOWN, LEFT, RIGHT, STAY, UP};
+pub fn solution_dijkstra(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let valley = parse(input)?;
+    let map = phase(PREPROCESS, || Map::new(&valley));
+    Ok(format!(
+        "{}, {}",
+        phase(PART_ONE, || part_one_dijkstra(&map))?,
+        phase(PART_TWO, || part_two_dijkstra(&map))?
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
 
-pub fn solution(input: &str) -> String {
-    let valley = parse(input);
-    format!("{}, {}", part_one(&valley), part_two(&valley))
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let valley = parse(input)?;
+    let printed = valley.to_string();
+    printed_back(&valley, parse(&printed))?;
+    Ok(printed)
+}
+
+/// A valley of about `size` grounds, four times as wide as high so that the blizzards repeat as
+/// soon as they cross it, with a third of the grounds under a blizzard. As in the puzzle, none
+/// blows up or down through the entrance or the exit.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let height = ((size / 4) as f64).sqrt().max(1.0) as usize;
+    let width = 4 * height;
+    let grounds = (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|x| match rng.below(12) {
+                    0 => Blizzard(Left),
+                    1 => Blizzard(Right),
+                    2 if x > 0 && x < width - 1 => Blizzard(Up),
+                    3 if x > 0 && x < width - 1 => Blizzard(Down),
+                    _ => Clear,
+                })
+                .collect()
+        })
+        .collect();
+
+    let valley = Valley {
+        grounds: Grid::from_rows(grounds).expect("a rectangle"),
+    };
+    valley.to_string()
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Dir {
     Up,
     Left,
@@ -36,38 +97,53 @@ impl Dir {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Ground {
     Blizzard(Dir),
     Clear,
 }
 use Ground::*;
 
+#[derive(Debug, PartialEq, Eq)]
 struct Valley {
-    width: usize,
-    height: usize,
-    grounds: Vec<Vec<Ground>>,
+    grounds: Grid<Ground>,
 }
 
-fn parse(input: &str) -> Valley {
-    let (_, valley) = all_consuming(p_valley)(input).expect("valid complete parse");
-    valley
+impl Display for Valley {
+    /// The valley with its walls, the entrance top left and the exit bottom right.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.grounds.width() as usize;
+        writeln!(f, "#.{}", "#".repeat(width))?;
+        for row in self.grounds.rows() {
+            let row: String = row
+                .iter()
+                .map(|g| match g {
+                    Blizzard(Up) => '^',
+                    Blizzard(Left) => '<',
+                    Blizzard(Down) => 'v',
+                    Blizzard(Right) => '>',
+                    Clear => '.',
+                })
+                .collect();
+            writeln!(f, "#{}#", row)?;
+        }
+        write!(f, "{}.#", "#".repeat(width))
+    }
+}
+
+fn parse(input: &str) -> Result<Valley, ParseError> {
+    let (_, grounds) = all_consuming(p_valley)(input)?;
+    let grounds =
+        Grid::from_rows(grounds).ok_or_else(|| ParseError::new("valley: not a rectangle"))?;
+    Ok(Valley { grounds })
 }
 
-fn p_valley(input: &str) -> IResult<&str, Valley> {
+fn p_valley(input: &str) -> IResult<&str, Vec<Vec<Ground>>> {
     let (input, _) = delimited(tag("#."), take_while1(|c: char| c == '#'), line_ending)(input)?;
     let (input, grounds) = many1(p_valley_line)(input)?;
     let (input, _) = tuple((take_while1(|c: char| c == '#'), tag(".#")))(input)?;
 
-    let width = grounds[0].len();
-    let height = grounds.len();
-
-    let valley = Valley {
-        width,
-        height,
-        grounds,
-    };
-    Ok((input, valley))
+    Ok((input, grounds))
 }
 
 fn p_valley_line(input: &str) -> IResult<&str, Vec<Ground>> {
@@ -94,45 +170,32 @@ fn lcm(a: usize, b: usize) -> usize {
     m
 }
 
-fn update_passing(valley: &Valley, mut coord: Coord, d: Coord, passing: &mut [Vec<Vec<bool>>]) {
-    let repeat = lcm(valley.width, valley.height);
-
-    for i in 0..repeat {
-        passing[coord.y as usize][coord.x as usize][i] = true;
-
-        coord = coord + d;
-        if coord.y >= valley.height as i64 {
-            coord.y = 0
-        } else if coord.y < 0 {
-            coord.y = (valley.height - 1) as i64
-        } else if coord.x >= valley.width as i64 {
-            coord.x = 0
-        } else if coord.x < 0 {
-            coord.x = (valley.width - 1) as i64
-        }
+/// Marks every epoch of a cycle at which the blizzard from `coord` heading `d` passes a ground.
+fn update_passing(mut coord: Coord, d: Coord, passing: &mut Grid<Vec<bool>>) {
+    let (width, height) = (passing.width(), passing.height());
+
+    for i in 0..passing[coord].len() {
+        passing[coord][i] = true;
+
+        let (x, y) = (coord + d).to_tuple();
+        coord = Coord::new(x.rem_euclid(width), y.rem_euclid(height));
     }
 }
 
 struct Map {
     repeat: usize,
-    passing: Vec<Vec<Vec<bool>>>,
+    passing: Grid<Vec<bool>>,
 }
 
 impl Map {
     fn new(valley: &Valley) -> Self {
-        let repeat = lcm(valley.width, valley.height);
-        let mut passing = vec![vec![vec![false; repeat]; valley.width]; valley.height];
-
-        for (y, row) in valley.grounds.iter().enumerate() {
-            for (x, &g) in row.iter().enumerate() {
-                if let Blizzard(d) = g {
-                    update_passing(
-                        valley,
-                        Coord::new(x as i64, y as i64),
-                        d.to_coord(),
-                        &mut passing,
-                    );
-                }
+        let grounds = &valley.grounds;
+        let repeat = lcm(grounds.width() as usize, grounds.height() as usize);
+        let mut passing = grounds.map(|_| vec![false; repeat]);
+
+        for (coord, &g) in grounds.iter() {
+            if let Blizzard(d) = g {
+                update_passing(coord, d.to_coord(), &mut passing);
             }
         }
 
@@ -140,11 +203,11 @@ impl Map {
     }
 
     fn width(&self) -> i64 {
-        self.passing[0].len() as i64
+        self.passing.width()
     }
 
     fn height(&self) -> i64 {
-        self.passing.len() as i64
+        self.passing.height()
     }
 
     fn start(&self) -> Coord {
@@ -155,31 +218,23 @@ impl Map {
         Coord::new(self.width() - 1, self.height())
     }
 
-    fn in_valley(&self, coord: Coord) -> bool {
-        coord.x >= 0 && coord.x < self.width() && coord.y >= 0 && coord.y < self.height()
-    }
-
     fn passible(&self, coord: Coord, epoch: usize) -> bool {
         coord == self.start()
             || coord == self.goal()
-            || (self.in_valley(coord)
-                && !self.passing[coord.y as usize][coord.x as usize][epoch % self.repeat])
+            || self
+                .passing
+                .get(coord)
+                .is_some_and(|passing| !passing[epoch % self.repeat])
     }
 
+    /// Epochs wrap around with the blizzards, so states a whole cycle apart are the same.
     fn moves(&self, actor: Expedition) -> Vec<(Expedition, i64)> {
+        let epoch = (actor.epoch + 1) % self.repeat;
         [UP, DOWN, LEFT, RIGHT, STAY]
             .into_iter()
             .map(|d| actor.coord + d)
-            .filter(|next| self.passible(*next, actor.epoch + 1))
-            .map(|next| {
-                (
-                    Expedition {
-                        coord: next,
-                        epoch: actor.epoch + 1,
-                    },
-                    1,
-                )
-            })
+            .filter(|next| self.passible(*next, epoch))
+            .map(|next| (Expedition { coord: next, epoch }, 1))
             .collect()
     }
 
@@ -228,15 +283,18 @@ impl Map {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+fn no_way() -> ParseError {
+    ParseError::new("no way through the blizzards")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Expedition {
     coord: Coord,
     epoch: usize,
 }
 
-fn part_one(valley: &Valley) -> i64 {
-    let map = Map::new(valley);
-    let (_path, shortest) = astar(
+fn part_one(map: &Map) -> Result<i64, ParseError> {
+    astar_plain(
         &Expedition {
             coord: map.start(),
             epoch: 0,
@@ -245,12 +303,24 @@ fn part_one(valley: &Valley) -> i64 {
         |state| map.heuristic(state.coord),
         |state| state.coord == map.goal(),
     )
-    .expect("shortest path");
+    .ok_or_else(no_way)
+}
 
-    shortest
+fn part_one_dijkstra(map: &Map) -> Result<i64, ParseError> {
+    let (_path, shortest) = dijkstra(
+        &Expedition {
+            coord: map.start(),
+            epoch: 0,
+        },
+        |state| map.moves(*state),
+        |state| state.coord == map.goal(),
+    )
+    .ok_or_else(no_way)?;
+
+    Ok(shortest)
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum ExpeditionStage {
     Init,
     FirstGoal,
@@ -258,16 +328,15 @@ enum ExpeditionStage {
 }
 use ExpeditionStage::*;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct TripleExpedition {
     coord: Coord,
     epoch: usize,
     stage: ExpeditionStage,
 }
 
-fn part_two(valley: &Valley) -> i64 {
-    let map = Map::new(valley);
-    let (_path, shortest) = astar(
+fn part_two(map: &Map) -> Result<i64, ParseError> {
+    astar_plain(
         &TripleExpedition {
             coord: map.start(),
             epoch: 0,
@@ -277,14 +346,28 @@ fn part_two(valley: &Valley) -> i64 {
         |state| map.triple_heuristic(*state),
         |state| state.stage == SecondStart && state.coord == map.goal(),
     )
-    .expect("shortest path");
+    .ok_or_else(no_way)
+}
 
-    shortest
+fn part_two_dijkstra(map: &Map) -> Result<i64, ParseError> {
+    let (_path, shortest) = dijkstra(
+        &TripleExpedition {
+            coord: map.start(),
+            epoch: 0,
+            stage: Init,
+        },
+        |state| map.triple_moves(*state),
+        |state| state.stage == SecondStart && state.coord == map.goal(),
+    )
+    .ok_or_else(no_way)?;
+
+    Ok(shortest)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::{instrumented, Admissibility};
 
     const INPUT: &str = "#.######
 #>>.<^<#
@@ -295,13 +378,29 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let valley = parse(INPUT);
-        assert_eq!(part_one(&valley), 18);
+        let map = Map::new(&parse(INPUT).unwrap());
+        assert_eq!(part_one(&map).unwrap(), 18);
+        assert_eq!(part_one_dijkstra(&map).unwrap(), 18);
     }
 
     #[test]
     fn example_part_two() {
-        let valley = parse(INPUT);
-        assert_eq!(part_two(&valley), 54);
+        let map = Map::new(&parse(INPUT).unwrap());
+        assert_eq!(part_two(&map).unwrap(), 54);
+        assert_eq!(part_two_dijkstra(&map).unwrap(), 54);
+    }
+
+    #[test]
+    fn heuristics_admissible() {
+        let map = Map::new(&parse(INPUT).unwrap());
+        let (_, reports) = instrumented(true, || (part_one(&map), part_two(&map)));
+        assert_eq!(reports.len(), 2);
+        for report in reports {
+            assert!(
+                matches!(report.heuristic, Some(Admissibility::Admissible(_))),
+                "{}",
+                report
+            );
+        }
     }
 }
@@ -1,8 +1,39 @@
 use std::{fmt::Display, str::from_utf8};
 
-pub fn solution(input: &str) -> String {
-    let snafus = parse(input);
-    part_one(&snafus)
+use crate::{
+    input::{printed_back, ParseError},
+    overflow::{Arith, Overflow},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let snafus = parse(input)?;
+    Ok(part_one(&snafus)?)
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let snafus = parse(input)?;
+    let printed = print(&snafus);
+    printed_back(&snafus, parse(&printed))?;
+    Ok(printed)
+}
+
+/// `size` SNAFU numbers of up to 12 digits.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let mut snafu = rng.pick(&['1', '2']).to_string();
+            snafu.extend((1..rng.range(1, 12)).map(|_| rng.pick(&['=', '-', '0', '1', '2'])));
+            snafu
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 const SNAFU_RADIX: i64 = 5;
@@ -12,38 +43,43 @@ const SNAFU_OFFSET: i64 = 2;
 struct Snafu(i64);
 
 impl Snafu {
-    fn parse(raw: &str) -> Self {
-        let mut n = match raw.as_bytes()[0] {
-            b'1' => 1,
-            b'2' => 2,
-            _ => unreachable!("valid input"),
+    fn parse(raw: &str) -> Result<Self, ParseError> {
+        let invalid = || ParseError::new(format!("snafu: {:?}", raw));
+
+        let mut n: i64 = match raw.as_bytes().first() {
+            Some(b'1') => 1,
+            Some(b'2') => 2,
+            _ => return Err(invalid()),
         };
 
-        n = raw.bytes().skip(1).fold(n, |mut n, c| {
-            n *= SNAFU_RADIX;
-            n -= SNAFU_OFFSET;
-            n += match c {
+        for c in raw.bytes().skip(1) {
+            let digit = match c {
                 b'=' => 0,
                 b'-' => 1,
                 b'0' => 2,
                 b'1' => 3,
                 b'2' => 4,
-                _ => unreachable!("valid input"),
+                _ => return Err(invalid()),
             };
-            n
-        });
 
-        Snafu(n)
+            n = n
+                .checked_mul(SNAFU_RADIX)
+                .and_then(|n| n.checked_add(digit - SNAFU_OFFSET))
+                .ok_or_else(invalid)?;
+        }
+
+        Ok(Snafu(n))
     }
 }
 
 impl Display for Snafu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut buffer = vec![];
-        let mut n = self.0;
+        // widened, the offset would overflow numbers close to the maximum
+        let mut n = i128::from(self.0);
         while n > 0 {
-            n += SNAFU_OFFSET;
-            let c = match n % SNAFU_RADIX {
+            n += i128::from(SNAFU_OFFSET);
+            let c = match n % i128::from(SNAFU_RADIX) {
                 0 => b'=',
                 1 => b'-',
                 2 => b'0',
@@ -54,7 +90,7 @@ impl Display for Snafu {
 
             buffer.push(c);
 
-            n /= SNAFU_RADIX;
+            n /= i128::from(SNAFU_RADIX);
         }
 
         buffer.reverse();
@@ -62,13 +98,21 @@ impl Display for Snafu {
     }
 }
 
-fn parse(input: &str) -> Vec<Snafu> {
+fn parse(input: &str) -> Result<Vec<Snafu>, ParseError> {
     input.lines().map(Snafu::parse).collect()
 }
 
-fn part_one(snafus: &[Snafu]) -> String {
-    let n = snafus.iter().map(|s| s.0).sum::<i64>();
-    format!("{}", Snafu(n))
+fn print(snafus: &[Snafu]) -> String {
+    snafus
+        .iter()
+        .map(Snafu::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn part_one(snafus: &[Snafu]) -> Result<String, Overflow> {
+    let n = Arith::new(25, "fuel requirements sum").sum(snafus.iter().map(|s| s.0))?;
+    Ok(format!("{}", Snafu(n)))
 }
 
 #[cfg(test)]
@@ -96,20 +140,23 @@ mod tests {
         assert_eq!(format!("{}", Snafu(3)), "1=");
         assert_eq!(format!("{}", Snafu(2022)), "1=11-2");
         assert_eq!(format!("{}", Snafu(314159265)), "1121-1110-1=0");
+
+        let max = Snafu(i64::MAX).to_string();
+        assert_eq!(Snafu::parse(&max).unwrap(), Snafu(i64::MAX));
     }
 
     #[test]
     fn example_snafu_to_number() {
-        assert_eq!(Snafu::parse("1"), Snafu(1));
-        assert_eq!(Snafu::parse("2"), Snafu(2));
-        assert_eq!(Snafu::parse("1="), Snafu(3));
-        assert_eq!(Snafu::parse("1=11-2"), Snafu(2022));
-        assert_eq!(Snafu::parse("1121-1110-1=0"), Snafu(314159265));
+        assert_eq!(Snafu::parse("1").unwrap(), Snafu(1));
+        assert_eq!(Snafu::parse("2").unwrap(), Snafu(2));
+        assert_eq!(Snafu::parse("1=").unwrap(), Snafu(3));
+        assert_eq!(Snafu::parse("1=11-2").unwrap(), Snafu(2022));
+        assert_eq!(Snafu::parse("1121-1110-1=0").unwrap(), Snafu(314159265));
     }
 
     #[test]
     fn example_part_one() {
-        let snafus = parse(INPUT);
-        assert_eq!(part_one(&snafus), "2=-1=0");
+        let snafus = parse(INPUT).unwrap();
+        assert_eq!(part_one(&snafus).unwrap(), "2=-1=0");
     }
 }
@@ -1,8 +1,50 @@
 use std::collections::{HashMap, HashSet};
 
-pub fn solution(input: &str) -> String {
-    let rucksacks = parse(input);
-    format!("{}, {}", part_one(&rucksacks), part_two(&rucksacks))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let rucksacks = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&rucksacks)?,
+        part_two(&rucksacks)?
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let rucksacks = parse(input)?;
+    let printed = print(&rucksacks);
+    printed_back(&rucksacks, parse(&printed))?;
+    Ok(printed)
+}
+
+/// About `size` rucksacks in groups of three, every line drawing its items from its own letters
+/// so the only shared items are the intended ones.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let mut lines = vec![];
+    for _ in 0..(size / 3).max(1) {
+        let badge = rng.pick(b"yz");
+        for k in 0..3u8 {
+            let own = |rng: &mut Rng, from: u8| b'a' + 8 * k + from + rng.below(4) as u8;
+            let shared = b'B' + 8 * k + rng.below(8) as u8;
+            let mut fst = vec![shared, badge];
+            fst.extend((0..4).map(|_| own(rng, 0)));
+            let mut snd = vec![shared];
+            snd.extend((0..5).map(|_| own(rng, 4)));
+            fst.extend(snd);
+            lines.push(String::from_utf8(fst).expect("ASCII"));
+        }
+    }
+    lines.join("\n")
 }
 
 fn priority(item: u8) -> u32 {
@@ -13,23 +55,37 @@ fn priority(item: u8) -> u32 {
     }
 }
 
+#[derive(PartialEq)]
 struct Rucksack<'a> {
     fst: &'a [u8],
     snd: &'a [u8],
 }
 
-fn parse(input: &str) -> Vec<Rucksack> {
+fn parse(input: &str) -> Result<Vec<Rucksack<'_>>, ParseError> {
     input
         .lines()
         .map(|l| {
             let b = l.as_bytes();
+            if b.len() % 2 != 0 || !b.iter().all(u8::is_ascii_alphabetic) {
+                return Err(ParseError::new(format!("rucksack: {:?}", l)));
+            }
+
             let (fst, snd) = b.split_at(b.len() / 2);
-            Rucksack { fst, snd }
+            Ok(Rucksack { fst, snd })
         })
         .collect()
 }
 
-fn part_one(rucksacks: &[Rucksack]) -> u32 {
+fn print(rucksacks: &[Rucksack]) -> String {
+    rucksacks
+        .iter()
+        .map(|sack| [sack.fst, sack.snd].concat())
+        .map(|items| String::from_utf8(items).expect("ASCII letters"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn part_one(rucksacks: &[Rucksack]) -> Result<u32, ParseError> {
     rucksacks
         .iter()
         .map(|sack| {
@@ -38,14 +94,14 @@ fn part_one(rucksacks: &[Rucksack]) -> u32 {
                 .snd
                 .iter()
                 .find(|item| set.contains(item))
-                .expect("valid input");
+                .ok_or_else(|| ParseError::new("rucksack without an item in both compartments"))?;
 
-            priority(*item)
+            Ok(priority(*item))
         })
         .sum()
 }
 
-fn part_two(rucksacks: &[Rucksack]) -> u32 {
+fn part_two(rucksacks: &[Rucksack]) -> Result<u32, ParseError> {
     rucksacks
         .chunks_exact(3)
         .map(|sacks| {
@@ -67,9 +123,9 @@ fn part_two(rucksacks: &[Rucksack]) -> u32 {
                         }
                     },
                 )
-                .expect("valid input");
+                .ok_or_else(|| ParseError::new("group of rucksacks without a badge"))?;
 
-            priority(badge)
+            Ok(priority(badge))
         })
         .sum()
 }
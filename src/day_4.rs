@@ -1,17 +1,55 @@
-pub fn solution(input: &str) -> String {
-    let assignments = parse(input);
-    format!("{}, {}", part_one(&assignments), part_two(&assignments))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let assignments = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        part_one(&assignments),
+        part_two(&assignments)
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
 }
 
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let assignments = parse(input)?;
+    let printed = print(&assignments);
+    printed_back(&assignments, parse(&printed))?;
+    Ok(printed)
+}
+
+/// `size` pairs of section assignments.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let (a, c) = (rng.range(1, 90), rng.range(1, 90));
+            let (b, d) = (rng.range(a, 99), rng.range(c, 99));
+            format!("{}-{},{}-{}", a, b, c, d)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(PartialEq)]
 struct Range {
     min: u32,
     max: u32,
 }
 
 impl Range {
-    fn new(min: u32, max: u32) -> Self {
-        assert!(min <= max);
-        Self { min, max }
+    fn new(min: u32, max: u32) -> Option<Self> {
+        if min <= max {
+            Some(Self { min, max })
+        } else {
+            None
+        }
     }
 
     fn covers(&self, other: &Self) -> bool {
@@ -23,21 +61,32 @@ impl Range {
     }
 }
 
-fn parse(input: &str) -> Vec<(Range, Range)> {
-    let regex = regex::Regex::new(r"(\d+)-(\d+),(\d+)-(\d+)").unwrap();
+fn parse(input: &str) -> Result<Vec<(Range, Range)>, ParseError> {
+    let regex = regex::Regex::new(r"^(\d+)-(\d+),(\d+)-(\d+)$").unwrap();
 
     input
         .lines()
         .map(|l| {
-            let cap = regex.captures(l).unwrap();
-            let range_0 = Range::new(cap[1].parse().unwrap(), cap[2].parse().unwrap());
-            let range_1 = Range::new(cap[3].parse().unwrap(), cap[4].parse().unwrap());
+            let range = |min: &str, max: &str| Range::new(min.parse().ok()?, max.parse().ok()?);
+            let invalid = || ParseError::new(format!("assignment: {:?}", l));
+
+            let cap = regex.captures(l).ok_or_else(invalid)?;
+            let range_0 = range(&cap[1], &cap[2]).ok_or_else(invalid)?;
+            let range_1 = range(&cap[3], &cap[4]).ok_or_else(invalid)?;
 
-            (range_0, range_1)
+            Ok((range_0, range_1))
         })
         .collect()
 }
 
+fn print(assignments: &[(Range, Range)]) -> String {
+    assignments
+        .iter()
+        .map(|(r0, r1)| format!("{}-{},{}-{}", r0.min, r0.max, r1.min, r1.max))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn part_one(assignments: &[(Range, Range)]) -> usize {
     assignments
         .iter()
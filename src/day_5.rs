@@ -1,85 +1,197 @@
+use std::fmt::{self, Display};
+
 use regex::Regex;
 
-pub fn solution(input: &str) -> String {
-    let (crates, steps) = parse(input);
-    format!(
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let (crates, steps) = parse(input)?;
+    Ok(format!(
         "{}, {}",
-        part_one(&crates, &steps),
-        part_two(&crates, &steps)
-    )
+        part_one(&crates, &steps)?,
+        part_two(&crates, &steps)?
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let (supplies, steps) = parse(input)?;
+    let printed = print(&supplies, &steps);
+    printed_back(&(supplies, steps), parse(&printed))?;
+    Ok(printed)
+}
+
+/// Nine stacks of crates rearranged in `size` steps, none of which empties a stack.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let mut stacks: Vec<Vec<u8>> = (0..9)
+        .map(|_| {
+            (0..rng.range(2, 8))
+                .map(|_| rng.pick(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ"))
+                .collect()
+        })
+        .collect();
+    let supplies = Supplies(stacks.clone());
+
+    // there are more crates than stacks, so some stack always has one to spare
+    let steps: Vec<Step> = (0..size.max(1))
+        .map(|_| {
+            let from = loop {
+                let from = rng.below(9) as usize;
+                if stacks[from].len() >= 2 {
+                    break from;
+                }
+            };
+            let to = (from + rng.range(1, 8) as usize) % 9;
+            let repeat = rng.range(1, stacks[from].len() as i64 - 1) as usize;
+
+            let cut = stacks[from].len() - repeat;
+            let moved = stacks[from].split_off(cut);
+            stacks[to].extend(moved);
+            Step { repeat, from, to }
+        })
+        .collect();
+
+    print(&supplies, &steps)
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Supplies(Vec<Vec<u8>>);
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Step {
     repeat: usize,
     from: usize,
     to: usize,
 }
 
-fn parse(input: &str) -> (Supplies, Vec<Step>) {
-    let line_len = input.find('\n').expect("valid input");
-    let supplies_len = (line_len + 1) / 4;
-    let mut supplies: Vec<Vec<u8>> = vec![vec![]; supplies_len];
+impl Display for Supplies {
+    /// The drawing as in the puzzle, every line padded to the full width.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let crates: Vec<String> = self
+                .0
+                .iter()
+                .map(|s| match s.get(level) {
+                    Some(&c) => format!("[{}]", char::from(c)),
+                    None => "   ".to_owned(),
+                })
+                .collect();
+            writeln!(f, "{}", crates.join(" "))?;
+        }
 
-    let mut lines = input.lines();
+        let numbers: Vec<String> = (1..=self.0.len()).map(|n| format!(" {} ", n)).collect();
+        write!(f, "{}", numbers.join(" "))
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.repeat,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
 
-    for l in (&mut lines).take_while(|l| l.starts_with('[')) {
+fn parse(input: &str) -> Result<(Supplies, Vec<Step>), ParseError> {
+    let (drawing, procedure) = input
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::new("no procedure after the drawing"))?;
+
+    // read the drawing bottom up, starting from the line of stack numbers
+    let mut drawing = drawing.lines().rev();
+    let supplies_len = drawing
+        .next()
+        .ok_or_else(|| ParseError::new("no stack numbers"))?
+        .split_whitespace()
+        .count();
+    let mut supplies: Vec<Vec<u8>> = vec![vec![]; supplies_len];
+
+    for l in drawing {
+        // lines may be cut short when the rightmost stacks are lower
         let b = l.as_bytes();
-        for (supply_idx, line_idx) in (1usize..line_len).step_by(4).enumerate() {
-            let c = b[line_idx];
-            if c.is_ascii_uppercase() {
-                supplies[supply_idx].push(c);
+        for (supply_idx, supply) in supplies.iter_mut().enumerate() {
+            match b.get(supply_idx * 4 + 1) {
+                Some(&c) if c.is_ascii_uppercase() => supply.push(c),
+                _ => (),
             }
         }
     }
 
-    for c in supplies.iter_mut() {
-        c.reverse();
-    }
+    let step_pattern = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
+    let steps = procedure
+        .lines()
+        .map(|l| {
+            let invalid = || ParseError::new(format!("step: {:?}", l));
+            let stack = |n: &str| {
+                n.parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .filter(|n| *n < supplies_len)
+            };
+
+            let caps = step_pattern.captures(l).ok_or_else(invalid)?;
+            let repeat = caps[1].parse().map_err(|_| invalid())?;
+            let from = stack(&caps[2]).ok_or_else(invalid)?;
+            let to = stack(&caps[3]).ok_or_else(invalid)?;
+
+            if from == to {
+                return Err(invalid());
+            }
 
-    assert_eq!(lines.next(), Some(""));
+            Ok(Step { repeat, from, to })
+        })
+        .collect::<Result<_, _>>()?;
 
-    let step_pattern = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
-    let steps = lines
-        .map(|l| {
-            let caps = step_pattern.captures(l).expect("valid input");
-            let repeat = caps[1].parse().unwrap();
-            let from = caps[2].parse::<usize>().unwrap() - 1;
-            let to = caps[3].parse::<usize>().unwrap() - 1;
+    Ok((Supplies(supplies), steps))
+}
 
-            assert_ne!(from, to);
+fn print(supplies: &Supplies, steps: &[Step]) -> String {
+    let steps: Vec<String> = steps.iter().map(Step::to_string).collect();
+    format!("{}\n\n{}", supplies, steps.join("\n"))
+}
 
-            Step { repeat, from, to }
+/// The crates on top of each stack, none of which may end up empty.
+fn tops(supplies: &[Vec<u8>]) -> Result<String, ParseError> {
+    supplies
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let c = *s
+                .last()
+                .ok_or_else(|| ParseError::new(format!("stack {} ends up empty", i + 1)))?;
+            Ok(char::from(c))
         })
-        .collect();
-
-    (Supplies(supplies), steps)
+        .collect()
 }
 
-fn part_one(supplies: &Supplies, steps: &[Step]) -> String {
+fn part_one(supplies: &Supplies, steps: &[Step]) -> Result<String, ParseError> {
     let mut supplies = supplies.0.clone();
 
     for &Step { repeat, from, to } in steps {
-        for _ in 0..repeat {
+        for _ in 0..repeat.min(supplies[from].len()) {
             if let Some(c) = supplies[from].pop() {
                 supplies[to].push(c);
             }
         }
     }
 
-    supplies
-        .iter()
-        .map(|s| {
-            let c = *s.last().expect("at least one crate each supply");
-            char::from(c)
-        })
-        .collect()
+    tops(&supplies)
 }
 
-fn part_two(supplies: &Supplies, steps: &[Step]) -> String {
+fn part_two(supplies: &Supplies, steps: &[Step]) -> Result<String, ParseError> {
     let mut supplies = supplies.0.clone();
 
     for &Step { repeat, from, to } in steps {
@@ -89,11 +201,38 @@ fn part_two(supplies: &Supplies, steps: &[Step]) -> String {
         supplies[to].extend_from_slice(&cut);
     }
 
-    supplies
-        .iter()
-        .map(|s| {
-            let c = *s.last().expect("at least one crate each supply");
-            char::from(c)
-        })
-        .collect()
+    tops(&supplies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn example_part_one() {
+        let (supplies, steps) = parse(INPUT).unwrap();
+        assert_eq!(part_one(&supplies, &steps).unwrap(), "CMZ");
+    }
+
+    #[test]
+    fn example_part_two() {
+        let (supplies, steps) = parse(INPUT).unwrap();
+        assert_eq!(part_two(&supplies, &steps).unwrap(), "MCD");
+    }
+
+    #[test]
+    fn normalized_example() {
+        let (supplies, steps) = parse(&crate::input::normalize(INPUT)).unwrap();
+        assert_eq!(part_one(&supplies, &steps).unwrap(), "CMZ");
+    }
 }
@@ -1,7 +1,46 @@
 use std::collections::HashMap;
 
-pub fn solution(input: &str) -> String {
-    format!("{}, {}", part_one(input), part_two(input))
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::Rng,
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let signal = parse(input)?;
+    Ok(format!("{}, {}", part_one(signal)?, part_two(signal)?))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let signal = parse(input)?;
+    let printed = String::from_utf8_lossy(signal).into_owned();
+    printed_back(&signal, parse(&printed))?;
+    Ok(printed)
+}
+
+/// A datastream of `size` characters with both markers at its very end.
+pub fn generate(size: usize, _rng: &mut Rng) -> String {
+    const TAIL: &str = "cdefghijklmnop";
+    let mut stream: String = "ab"
+        .chars()
+        .cycle()
+        .take(size.saturating_sub(TAIL.len()))
+        .collect();
+    stream.push_str(TAIL);
+    stream
+}
+
+fn parse(input: &str) -> Result<&[u8], ParseError> {
+    if input.bytes().all(|b| b.is_ascii_lowercase()) {
+        Ok(input.as_bytes())
+    } else {
+        Err(ParseError::new("datastream buffer: lowercase letters only"))
+    }
 }
 
 fn no_repeat(bytes: &[u8]) -> bool {
@@ -11,19 +50,18 @@ fn no_repeat(bytes: &[u8]) -> bool {
         .all(|(i, b0)| bytes.iter().skip(i + 1).all(|b1| b1 != b0))
 }
 
-fn part_one(input: &str) -> usize {
-    input
-        .as_bytes()
-        .windows(4)
-        .position(no_repeat)
-        .expect("valid input")
-        + 4
+fn no_marker() -> ParseError {
+    ParseError::new("datastream without a marker")
+}
+
+fn part_one(bytes: &[u8]) -> Result<usize, ParseError> {
+    let start = bytes.windows(4).position(no_repeat).ok_or_else(no_marker)?;
+    Ok(start + 4)
 }
 
-fn part_two(input: &str) -> usize {
+fn part_two(bytes: &[u8]) -> Result<usize, ParseError> {
     const MESSAGE_MARKER: usize = 14;
 
-    let bytes = input.as_bytes();
     let mut freq: HashMap<u8, u32> = HashMap::new();
 
     for &b in bytes.iter().take(MESSAGE_MARKER - 1) {
@@ -33,7 +71,7 @@ fn part_two(input: &str) -> usize {
     for i in MESSAGE_MARKER - 1..bytes.len() {
         *freq.entry(bytes[i]).or_default() += 1;
         if freq.len() == MESSAGE_MARKER {
-            return i + 1;
+            return Ok(i + 1);
         }
         let rm = bytes[i + 1 - MESSAGE_MARKER];
         let entry = freq.entry(rm).or_default();
@@ -43,5 +81,5 @@ fn part_two(input: &str) -> usize {
         }
     }
 
-    unreachable!("there must be a solution")
+    Err(no_marker())
 }
@@ -1,37 +1,103 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while},
     character::complete::{newline, space1, u64},
+    combinator::all_consuming,
     multi::{separated_list0, separated_list1},
     sequence::{preceded, separated_pair},
     IResult, Parser,
 };
 
-pub fn solution(input: &str) -> String {
-    let command_and_result = parse(input);
-    format!(
+use crate::{
+    input::{printed_back, ParseError},
+    params::{Param, Params},
+    registry::SolveError,
+    timing::{phase, PART_ONE, PART_TWO, PREPROCESS},
+    utils::Rng,
+};
+
+pub const PARAMS: &[Param] = &[
+    Param::new("small_dir", 100_000).at_least(0),
+    Param::new("disk_size", 70_000_000).at_least(0),
+    Param::new("update_size", 30_000_000).at_least(0),
+];
+
+pub fn solution(input: &str, params: &Params) -> Result<String, SolveError> {
+    let command_and_result = parse(input)?;
+    let dir_sizes = phase(PREPROCESS, || dir_sizes(&command_and_result));
+    Ok(format!(
         "{}, {}",
-        part_one(&command_and_result),
-        part_two(&command_and_result)
-    )
+        phase(PART_ONE, || part_one(
+            &dir_sizes,
+            params.get("small_dir") as u64
+        )),
+        phase(PART_TWO, || part_two(
+            &dir_sizes,
+            params.get("disk_size") as u64,
+            params.get("update_size") as u64
+        ))?
+    ))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let command_and_result = parse(input)?;
+    let printed = print(&command_and_result);
+    printed_back(&command_and_result, parse(&printed))?;
+    Ok(printed)
+}
+
+/// A terminal session exploring `size` directories, each one a child of a random earlier one.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    fn explore(dir: usize, children: &[Vec<usize>], rng: &mut Rng, out: &mut Vec<String>) {
+        out.push("$ ls".to_owned());
+        for child in &children[dir] {
+            out.push(format!("dir d{}", child));
+        }
+        for file in 0..rng.range(1, 3) {
+            out.push(format!("{} f{}", rng.range(1000, 300_000), file));
+        }
+        for &child in &children[dir] {
+            out.push(format!("$ cd d{}", child));
+            explore(child, children, rng, out);
+            out.push("$ cd ..".to_owned());
+        }
+    }
+
+    let mut children = vec![vec![]; size.max(1)];
+    for dir in 1..children.len() {
+        let parent = rng.below(dir as u64) as usize;
+        children[parent].push(dir);
+    }
+
+    let mut out = vec!["$ cd /".to_owned()];
+    explore(0, &children, rng, &mut out);
+    out.join("\n")
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum CdPath<'a> {
     Absolute(&'a str),
     Relative(&'a str),
     Parent,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum LsEntry<'a> {
     File(u64, &'a str),
     Dir(&'a str),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum CommandAndResult<'a> {
     Cd(CdPath<'a>),
     Ls(Vec<LsEntry<'a>>),
@@ -39,6 +105,27 @@ enum CommandAndResult<'a> {
 
 use CommandAndResult::*;
 
+impl Display for LsEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LsEntry::File(size, name) => write!(f, "{} {}", size, name),
+            LsEntry::Dir(name) => write!(f, "dir {}", name),
+        }
+    }
+}
+
+impl Display for CommandAndResult<'_> {
+    /// The command and its output as in the terminal session, without a trailing newline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cd(CdPath::Absolute(path) | CdPath::Relative(path)) => write!(f, "$ cd {}", path),
+            Cd(CdPath::Parent) => write!(f, "$ cd .."),
+            // an empty listing still ends the command's line
+            Ls(entries) => write!(f, "$ ls\n{}", entries.iter().join("\n")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct FSPath<'a>(Vec<&'a str>);
 
@@ -108,46 +195,52 @@ fn dir_sizes<'a>(command_and_result: &'a [CommandAndResult]) -> HashMap<FSPath<'
         .collect()
 }
 
-fn part_one(command_and_result: &[CommandAndResult]) -> u64 {
-    let dir_sizes = dir_sizes(command_and_result);
+fn part_one(dir_sizes: &HashMap<FSPath, u64>, small_dir: u64) -> u64 {
     dir_sizes
         .iter()
-        .map(|(_, &size)| if size <= 100000 { size } else { 0 })
+        .map(|(_, &size)| if size <= small_dir { size } else { 0 })
         .sum()
 }
 
-fn part_two(command_and_result: &[CommandAndResult]) -> u64 {
-    let dir_sizes = dir_sizes(command_and_result);
-    let total_size = dir_sizes[&FSPath::from_absolute("/")];
-    let required = 30_000_000 - (70_000_000 - total_size);
+fn part_two(
+    dir_sizes: &HashMap<FSPath, u64>,
+    disk_size: u64,
+    update_size: u64,
+) -> Result<u64, ParseError> {
+    let total_size = *dir_sizes
+        .get(&FSPath::from_absolute("/"))
+        .ok_or_else(|| ParseError::new("the root directory is never listed"))?;
+    let required = update_size.saturating_sub(disk_size.saturating_sub(total_size));
 
     dir_sizes
-        .into_values()
+        .values()
+        .copied()
         .filter(|&size| size >= required)
         .min()
-        .expect("valid input")
+        .ok_or_else(|| ParseError::new("no directory frees enough space"))
 }
 
-fn parse(input: &str) -> Vec<CommandAndResult> {
-    let (remain, command_and_result) = p_input(input).expect("valid input");
-    if !remain.is_empty() {
-        panic!("parse incomplete");
-    }
-    command_and_result
+fn parse(input: &str) -> Result<Vec<CommandAndResult<'_>>, ParseError> {
+    let (_, command_and_result) = all_consuming(p_input)(input)?;
+    Ok(command_and_result)
+}
+
+fn print(command_and_result: &[CommandAndResult]) -> String {
+    command_and_result.iter().join("\n")
 }
 
-fn p_input(input: &str) -> IResult<&str, Vec<CommandAndResult>> {
+fn p_input(input: &str) -> IResult<&str, Vec<CommandAndResult<'_>>> {
     separated_list1(newline, p_command_and_result)(input)
 }
 
-fn p_command_and_result(input: &str) -> IResult<&str, CommandAndResult> {
+fn p_command_and_result(input: &str) -> IResult<&str, CommandAndResult<'_>> {
     alt((
         preceded(tag("$ cd "), p_cd_path).map(CommandAndResult::Cd),
         preceded(tag("$ ls\n"), p_ls_result).map(CommandAndResult::Ls),
     ))(input)
 }
 
-fn p_cd_path(input: &str) -> IResult<&str, CdPath> {
+fn p_cd_path(input: &str) -> IResult<&str, CdPath<'_>> {
     let (input, path) = take_while(|c: char| c != '\n')(input)?;
     let cd_path = match path {
         ".." => CdPath::Parent,
@@ -158,11 +251,11 @@ fn p_cd_path(input: &str) -> IResult<&str, CdPath> {
     Ok((input, cd_path))
 }
 
-fn p_ls_result(input: &str) -> IResult<&str, Vec<LsEntry>> {
+fn p_ls_result(input: &str) -> IResult<&str, Vec<LsEntry<'_>>> {
     separated_list0(newline, p_ls_entry)(input)
 }
 
-fn p_ls_entry(input: &str) -> IResult<&str, LsEntry> {
+fn p_ls_entry(input: &str) -> IResult<&str, LsEntry<'_>> {
     alt((
         preceded(tag("dir "), take_while(|c: char| c != '\n')).map(LsEntry::Dir),
         separated_pair(u64, space1, take_while(|c: char| c != '\n'))
@@ -1,102 +1,105 @@
-use std::{collections::HashSet, iter::from_fn};
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::{Coord, FastSet, Grid, Rng, DOWN, LEFT, RIGHT, UP},
+};
 
-use itertools::iproduct;
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let map = parse(input)?;
 
-pub fn solution(input: &str) -> String {
-    let map = parse(input);
+    Ok(format!("{}, {}", part_one(&map), part_two(&map)))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
 
-    format!("{}, {}", part_one(&map), part_two(&map))
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let map = parse(input)?;
+    let printed = print(&map);
+    printed_back(&map, parse(&printed))?;
+    Ok(printed)
 }
 
-fn add((x0, y0): (isize, isize), (x1, y1): (isize, isize)) -> (isize, isize) {
-    (x0 + x1, y0 + y1)
+/// A square map of about `size` trees.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let side = ((size as f64).sqrt() as usize).max(2);
+    (0..side)
+        .map(|_| (0..side).map(|_| rng.pick(b"0123456789") as char).collect())
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
+#[derive(PartialEq)]
 struct Map {
-    trees: Vec<Vec<u8>>,
+    trees: Grid<u8>,
 }
 
 impl Map {
-    fn width(&self) -> isize {
-        self.trees[0].len() as isize
+    fn width(&self) -> i64 {
+        self.trees.width()
     }
 
-    fn height(&self) -> isize {
-        self.trees.len() as isize
-    }
-
-    fn get(&self, (x, y): (isize, isize)) -> Option<u8> {
-        if x < 0 || y < 0 {
-            return None;
-        }
-
-        self.trees.get(y as usize)?.get(x as usize).copied()
+    fn height(&self) -> i64 {
+        self.trees.height()
     }
 
-    fn visible_trees(
-        &self,
-        from: (isize, isize),
-        step: (isize, isize),
-    ) -> impl Iterator<Item = (isize, isize)> + '_ {
+    fn visible_trees(&self, from: Coord, step: Coord) -> impl Iterator<Item = Coord> + '_ {
         let mut highest = None::<u8>;
-        let mut curr = from;
 
-        from_fn(move || loop {
-            let h = self.get(curr)?;
+        self.trees.ray(from, step).filter_map(move |(coord, &h)| {
             if highest < Some(h) {
                 highest = Some(h);
-                let ret = curr;
-                curr = add(curr, step);
-                return Some(ret);
+                Some(coord)
             } else {
-                curr = add(curr, step);
+                None
             }
         })
     }
 }
 
-fn parse(input: &str) -> Map {
-    let trees = input
-        .lines()
-        .map(|l| l.as_bytes().iter().map(|h| h - b'0').collect())
-        .collect();
-    Map { trees }
+fn parse(input: &str) -> Result<Map, ParseError> {
+    let trees = Grid::parse(input, |_, h| match h {
+        '0'..='9' => Ok(h as u8 - b'0'),
+        _ => Err(ParseError::new(format!("tree height: {:?}", h))),
+    })?;
+
+    Ok(Map { trees })
 }
 
-fn part_one(map: &Map) -> usize {
-    let mut visible = HashSet::new();
+fn print(map: &Map) -> String {
+    map.trees.to_string()
+}
 
-    visible.extend((0..map.width()).flat_map(|x| map.visible_trees((x, 0), (0, 1))));
-    visible
-        .extend((0..map.width()).flat_map(|x| map.visible_trees((x, map.height() - 1), (0, -1))));
-    visible.extend((0..map.height()).flat_map(|y| map.visible_trees((0, y), (1, 0))));
-    visible
-        .extend((0..map.height()).flat_map(|y| map.visible_trees((map.width() - 1, y), (-1, 0))));
+fn part_one(map: &Map) -> usize {
+    let mut visible = FastSet::default();
 
-    // let mut sorted = visible.iter().collect::<Vec<_>>();
-    // sorted.sort_unstable();
-    // println!("{:?}", sorted);
+    visible.extend((0..map.width()).flat_map(|x| map.visible_trees(Coord::new(x, 0), DOWN)));
+    visible.extend(
+        (0..map.width()).flat_map(|x| map.visible_trees(Coord::new(x, map.height() - 1), UP)),
+    );
+    visible.extend((0..map.height()).flat_map(|y| map.visible_trees(Coord::new(0, y), RIGHT)));
+    visible.extend(
+        (0..map.height()).flat_map(|y| map.visible_trees(Coord::new(map.width() - 1, y), LEFT)),
+    );
 
     visible.len()
 }
 
-fn view_to<I>(map: &Map, step: (isize, isize), iter: I) -> Vec<Vec<u32>>
+fn view_to<I>(map: &Map, step: Coord, iter: I) -> Grid<u32>
 where
-    I: IntoIterator<Item = (isize, isize)>,
+    I: IntoIterator<Item = Coord>,
 {
-    let mut view = vec![vec![0; map.width() as usize]; map.height() as usize];
+    let mut view = map.trees.map(|_| 0);
     for start in iter.into_iter() {
-        let (mut x, mut y) = start;
-        let mut idx = 0u32;
         let mut nearest = [0u32; 10];
 
-        while let Some(h) = map.get((x, y)) {
+        for (idx, (coord, &h)) in map.trees.ray(start, step).enumerate() {
+            let idx = idx as u32;
             let blocked = *nearest[h as usize..].iter().max().unwrap();
-            view[y as usize][x as usize] = idx - blocked;
+            view[coord] = idx - blocked;
             nearest[h as usize] = idx;
-
-            (x, y) = add((x, y), step);
-            idx += 1;
         }
     }
 
@@ -105,16 +108,14 @@ where
 
 #[rustfmt::skip]
 fn part_two(map: &Map) -> u32 {
-    let view_to_north = view_to(map, (0, 1), (0..map.width()).map(|x| (x, 0)));
-    let view_to_south = view_to( map, (0, -1), (0..map.width()).map(|x| (x, map.height() - 1)));
-    let view_to_west = view_to(map, (1, 0), (0..map.height()).map(|y| (0, y)));
-    let view_to_east = view_to( map, (-1, 0), (0..map.height()).map(|y| (map.width() - 1, y)));
-
-    iproduct!(0 .. map.width(), 0 .. map.height())
-        .map(|(x, y)| {
-            let (x, y) = (x as usize, y as usize);
-            view_to_north[y][x] * view_to_south[y][x] * view_to_west[y][x] * view_to_east[y][x]
-        })
+    let view_to_north = view_to(map, DOWN, (0..map.width()).map(|x| Coord::new(x, 0)));
+    let view_to_south = view_to(map, UP, (0..map.width()).map(|x| Coord::new(x, map.height() - 1)));
+    let view_to_west = view_to(map, RIGHT, (0..map.height()).map(|y| Coord::new(0, y)));
+    let view_to_east = view_to(map, LEFT, (0..map.height()).map(|y| Coord::new(map.width() - 1, y)));
+
+    map.trees
+        .coords()
+        .map(|c| view_to_north[c] * view_to_south[c] * view_to_west[c] * view_to_east[c])
         .max()
         .unwrap()
 }
@@ -131,13 +132,13 @@ mod tests {
 
     #[test]
     fn example_part_one() {
-        let map = parse(INPUT);
+        let map = parse(INPUT).unwrap();
         assert_eq!(part_one(&map), 21);
     }
 
     #[test]
     fn example_part_two() {
-        let map = parse(INPUT);
+        let map = parse(INPUT).unwrap();
         assert_eq!(part_two(&map), 8)
     }
 }
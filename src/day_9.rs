@@ -1,13 +1,50 @@
-use std::collections::HashSet;
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    hash::BuildHasher,
+};
+
+use crate::{
+    input::{printed_back, ParseError},
+    params::Params,
+    registry::SolveError,
+    utils::{Coord, FastState, Rng},
+};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let motions = parse(input)?;
+    Ok(format!("{}, {}", part_one(&motions), part_two(&motions)))
+}
 
-use crate::utils::Coord;
+/// The solution with the visited positions hashed by the standard SipHash, to compare against.
+pub fn solution_siphash(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let motions = parse(input)?;
+    Ok(format!(
+        "{}, {}",
+        simulate_knots::<2, RandomState>(&motions),
+        simulate_knots::<10, RandomState>(&motions)
+    ))
+}
 
-pub fn solution(input: &str) -> String {
-    let motions = parse(input);
-    format!("{}, {}", part_one(&motions), part_two(&motions))
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
 }
 
-#[derive(Clone, Copy)]
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    let motions = parse(input)?;
+    let printed = print(&motions);
+    printed_back(&motions, parse(&printed))?;
+    Ok(printed)
+}
+
+/// `size` random motions of the head.
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{} {}", rng.pick(&['U', 'D', 'L', 'R']), rng.range(1, 20)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Clone, Copy, PartialEq)]
 enum FourWay {
     U,
     L,
@@ -15,25 +52,42 @@ enum FourWay {
     R,
 }
 
-fn parse(input: &str) -> Vec<(FourWay, i64)> {
-    let regex = Regex::new(r"(?P<dir>U|D|L|R) (?P<steps>\d+)").unwrap();
+fn parse(input: &str) -> Result<Vec<(FourWay, i64)>, ParseError> {
+    let regex = Regex::new(r"^(?P<dir>U|D|L|R) (?P<steps>\d+)$").unwrap();
     input
         .lines()
         .map(|l| {
-            let caps = regex.captures(l).expect("valid input line");
+            let invalid = || ParseError::new(format!("motion: {:?}", l));
+            let caps = regex.captures(l).ok_or_else(invalid)?;
             let dir = match &caps["dir"] {
                 "U" => U,
                 "L" => L,
                 "D" => D,
                 "R" => R,
-                _ => unreachable!("valid direction"),
+                _ => unreachable!("filtered by regex"),
             };
-            let steps = caps["steps"].parse().expect("valid steps");
-            (dir, steps)
+            let steps = caps["steps"].parse().map_err(|_| invalid())?;
+            Ok((dir, steps))
         })
         .collect()
 }
 
+fn print(motions: &[(FourWay, i64)]) -> String {
+    motions
+        .iter()
+        .map(|(dir, steps)| {
+            let dir = match dir {
+                U => 'U',
+                L => 'L',
+                D => 'D',
+                R => 'R',
+            };
+            format!("{} {}", dir, steps)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 use FourWay::*;
 
 #[derive(Clone, Copy)]
@@ -47,7 +101,7 @@ use EightWay::*;
 
 impl Coord {
     fn follow4(self, dir: FourWay) -> Self {
-        let Coord { x, y } = self;
+        let (x, y) = self.to_tuple();
 
         let (nx, ny) = match dir {
             FourWay::U => (x, y + 1),
@@ -82,8 +136,8 @@ fn tail_heading(head: Coord, tail: Coord) -> Option<EightWay> {
     }
 }
 
-fn simulate_knots<const N: usize>(motions: &[(FourWay, i64)]) -> usize {
-    let mut visited = HashSet::new();
+fn simulate_knots<const N: usize, S: BuildHasher + Default>(motions: &[(FourWay, i64)]) -> usize {
+    let mut visited: HashSet<Coord, S> = HashSet::default();
     let mut knots = [Coord::default(); N];
     visited.insert(knots[N - 1]);
 
@@ -103,11 +157,11 @@ fn simulate_knots<const N: usize>(motions: &[(FourWay, i64)]) -> usize {
 }
 
 fn part_one(motions: &[(FourWay, i64)]) -> usize {
-    simulate_knots::<2>(motions)
+    simulate_knots::<2, FastState>(motions)
 }
 
 fn part_two(motions: &[(FourWay, i64)]) -> usize {
-    simulate_knots::<10>(motions)
+    simulate_knots::<10, FastState>(motions)
 }
 
 #[cfg(test)]
@@ -134,15 +188,15 @@ U 20";
 
     #[test]
     fn example_part_one() {
-        let motions = parse(INPUT);
+        let motions = parse(INPUT).unwrap();
         assert_eq!(part_one(&motions), 13);
     }
 
     #[test]
     fn example_part_two() {
-        let motions = parse(INPUT);
+        let motions = parse(INPUT).unwrap();
         assert_eq!(part_two(&motions), 1);
-        let motions = parse(LONG_INPUT);
+        let motions = parse(LONG_INPUT).unwrap();
         assert_eq!(part_two(&motions), 36);
     }
 }
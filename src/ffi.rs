@@ -0,0 +1,239 @@
+//! C bindings, built into the `cdylib`. `include/advent_2022.h` is generated from [`header`].
+
+use std::{
+    ffi::{c_char, CString},
+    fmt::Write,
+    panic, slice,
+};
+
+use crate::{
+    input::normalize,
+    registry::{self, SolveError},
+};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdventCode {
+    Ok = 0,
+    UnknownDay = 1,
+    UnknownPart = 2,
+    NullInput = 3,
+    InvalidUtf8 = 4,
+    InvalidInput = 5,
+    Panicked = 6,
+    Overflow = 7,
+}
+
+impl AdventCode {
+    const ALL: [AdventCode; 8] = [
+        AdventCode::Ok,
+        AdventCode::UnknownDay,
+        AdventCode::UnknownPart,
+        AdventCode::NullInput,
+        AdventCode::InvalidUtf8,
+        AdventCode::InvalidInput,
+        AdventCode::Panicked,
+        AdventCode::Overflow,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            AdventCode::Ok => "ADVENT_OK",
+            AdventCode::UnknownDay => "ADVENT_UNKNOWN_DAY",
+            AdventCode::UnknownPart => "ADVENT_UNKNOWN_PART",
+            AdventCode::NullInput => "ADVENT_NULL_INPUT",
+            AdventCode::InvalidUtf8 => "ADVENT_INVALID_UTF8",
+            AdventCode::InvalidInput => "ADVENT_INVALID_INPUT",
+            AdventCode::Panicked => "ADVENT_PANICKED",
+            AdventCode::Overflow => "ADVENT_OVERFLOW",
+        }
+    }
+}
+
+/// The answer when `code` is `Ok`, the error message otherwise. `text` is owned by the caller and
+/// must be released with [`advent_string_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct AdventAnswer {
+    pub code: AdventCode,
+    pub text: *mut c_char,
+}
+
+impl AdventAnswer {
+    fn new(code: AdventCode, text: &str) -> Self {
+        // answers never hold NUL, error messages might quote one from the input
+        let text = CString::new(text.replace('\0', "\\0")).expect("NUL replaced");
+        Self {
+            code,
+            text: text.into_raw(),
+        }
+    }
+}
+
+fn solve(day: u32, part: u32, input: &[u8]) -> AdventAnswer {
+    let registered = match registry::day(day as usize) {
+        Some(registered) => registered,
+        None => return AdventAnswer::new(AdventCode::UnknownDay, "no solution for this day"),
+    };
+    let input = match std::str::from_utf8(input) {
+        Ok(input) => normalize(input),
+        Err(_) => return AdventAnswer::new(AdventCode::InvalidUtf8, "input is not UTF-8"),
+    };
+
+    let answer = match (registered.solution)(&input, &registered.params()) {
+        Ok(answer) => answer,
+        Err(SolveError::Parse(e)) => {
+            return AdventAnswer::new(AdventCode::InvalidInput, &e.to_string())
+        }
+        Err(SolveError::Overflow(e)) => {
+            return AdventAnswer::new(AdventCode::Overflow, &e.to_string())
+        }
+    };
+    if part == 0 {
+        return AdventAnswer::new(AdventCode::Ok, &answer);
+    }
+
+    match registry::parts(&answer).get(part as usize - 1) {
+        Some(answer) => AdventAnswer::new(AdventCode::Ok, answer),
+        None => AdventAnswer::new(AdventCode::UnknownPart, "no such part for this day"),
+    }
+}
+
+/// Solves `part` of `day` for the `len` bytes of UTF-8 input at `input`, or both parts joined as
+/// the CLI prints them when `part` is 0.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes, it may be null only if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn advent_solve(
+    day: u32,
+    part: u32,
+    input: *const u8,
+    len: usize,
+) -> AdventAnswer {
+    let input = if len == 0 {
+        &[][..]
+    } else if input.is_null() {
+        return AdventAnswer::new(AdventCode::NullInput, "input is null");
+    } else {
+        slice::from_raw_parts(input, len)
+    };
+
+    // unwinding into C is undefined behavior
+    panic::catch_unwind(|| solve(day, part, input))
+        .unwrap_or_else(|_| AdventAnswer::new(AdventCode::Panicked, "solution panicked"))
+}
+
+/// Releases the text of an [`AdventAnswer`], null is ignored.
+///
+/// # Safety
+///
+/// `text` must come from [`advent_solve`] and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn advent_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// The C header declaring the bindings.
+pub fn header() -> String {
+    let mut h = String::new();
+    h.push_str("/* Generated from src/ffi.rs, regenerate with UPDATE_HEADER=1 cargo test. */\n\n");
+    h.push_str("#ifndef ADVENT_2022_H\n#define ADVENT_2022_H\n\n");
+    h.push_str("#include <stddef.h>\n#include <stdint.h>\n\n");
+    h.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+
+    h.push_str("typedef enum AdventCode {\n");
+    for code in AdventCode::ALL {
+        writeln!(h, "    {} = {},", code.name(), code as i32).unwrap();
+    }
+    h.push_str("} AdventCode;\n\n");
+
+    h.push_str("/* The answer when code is ADVENT_OK, the error message otherwise.\n");
+    h.push_str(" * text is owned by the caller, release it with advent_string_free. */\n");
+    h.push_str(
+        "typedef struct AdventAnswer {\n    AdventCode code;\n    char *text;\n} AdventAnswer;\n\n",
+    );
+
+    h.push_str("/* Solves part (1 or 2) of day for len bytes of UTF-8 input, or both parts\n");
+    h.push_str(" * joined when part is 0. input may be NULL only if len is 0. */\n");
+    h.push_str(
+        "AdventAnswer advent_solve(uint32_t day, uint32_t part, const uint8_t *input, size_t len);\n\n",
+    );
+    h.push_str("/* Releases the text of an AdventAnswer, NULL is ignored. */\n");
+    h.push_str("void advent_string_free(char *text);\n\n");
+
+    h.push_str("#ifdef __cplusplus\n}\n#endif\n\n#endif /* ADVENT_2022_H */\n");
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CStr, ptr};
+
+    use super::*;
+
+    fn call(day: u32, part: u32, input: &str) -> (AdventCode, String) {
+        unsafe {
+            let answer = advent_solve(day, part, input.as_ptr(), input.len());
+            let text = CStr::from_ptr(answer.text).to_str().unwrap().to_owned();
+            advent_string_free(answer.text);
+            (answer.code, text)
+        }
+    }
+
+    #[test]
+    fn solves_parts() {
+        let input = "1000\r\n2000\r\n\r\n3000\r\n";
+        assert_eq!(call(1, 1, input), (AdventCode::Ok, "3000".to_owned()));
+        assert_eq!(call(1, 2, input), (AdventCode::Ok, "6000".to_owned()));
+        assert_eq!(call(1, 0, input), (AdventCode::Ok, "3000, 6000".to_owned()));
+        assert_eq!(call(25, 1, "1=\n2").0, AdventCode::Ok);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(call(26, 1, "").0, AdventCode::UnknownDay);
+        assert_eq!(call(1, 3, "1").0, AdventCode::UnknownPart);
+        assert_eq!(call(25, 2, "1=").0, AdventCode::UnknownPart);
+        assert_eq!(call(2, 1, "A Q\0").0, AdventCode::InvalidInput);
+
+        unsafe {
+            let answer = advent_solve(1, 1, [0xff, 0xfe].as_ptr(), 2);
+            assert_eq!(answer.code, AdventCode::InvalidUtf8);
+            advent_string_free(answer.text);
+
+            let answer = advent_solve(1, 1, ptr::null(), 4);
+            assert_eq!(answer.code, AdventCode::NullInput);
+            advent_string_free(answer.text);
+
+            advent_string_free(ptr::null_mut());
+        }
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn overflow_reported() {
+        let snafu = "2".repeat(27);
+        let input = [snafu.as_str(); 3].join("\n");
+        let (code, text) = call(25, 1, &input);
+        assert_eq!(code, AdventCode::Overflow);
+        assert!(text.starts_with("day 25: overflow"));
+    }
+
+    #[test]
+    fn header_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/advent_2022.h");
+        if std::env::var_os("UPDATE_HEADER").is_some() {
+            std::fs::write(path, header()).unwrap();
+        }
+        let committed = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            committed == header(),
+            "{} is out of date, regenerate it with UPDATE_HEADER=1 cargo test",
+            path
+        );
+    }
+}
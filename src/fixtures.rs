@@ -0,0 +1,77 @@
+use std::{error::Error, fs, path::Path};
+
+use crate::{input::normalize, params::Params, registry::Day};
+
+/// An example from a puzzle text, stored under `fixtures/day_N/` as `NAME.in` with the expected
+/// answer in `NAME.out` and, optionally, the parameters it needs in `NAME.params`, one
+/// `name=value` per line. The day's renderings of it are kept beside it as snapshots, see
+/// [`crate::snapshot`].
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub name: String,
+    pub input: String,
+    pub answer: String,
+    pub params: Vec<String>,
+}
+
+impl Fixture {
+    /// The day's parameters with the fixture's overrides applied.
+    pub fn params(&self, day: &Day) -> Result<Params, String> {
+        let mut params = day.params();
+        for assignment in &self.params {
+            params.set(assignment)?;
+        }
+        Ok(params)
+    }
+
+    /// Whether a solution's answer matches the expected one, ignoring trailing whitespace.
+    pub fn expects(&self, answer: &str) -> bool {
+        normalize(answer) == self.answer
+    }
+}
+
+/// All fixtures of a day found under `root`, sorted by name.
+pub fn load(root: &Path, day: usize) -> Result<Vec<Fixture>, Box<dyn Error>> {
+    let dir = root.join(format!("day_{}", day));
+    if !dir.is_dir() {
+        return Err(format!("no fixtures for day {} in {}", day, root.display()).into());
+    }
+
+    let mut names = vec![];
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "in") {
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let read = |ext: &str| fs::read_to_string(dir.join(format!("{}.{}", name, ext)));
+            let input = normalize(&read("in")?);
+            let answer = normalize(
+                &read("out").map_err(|e| format!("no answer to {} of day {}: {}", name, day, e))?,
+            );
+            let params = match read("params") {
+                Ok(params) => params
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+                Err(_) => vec![],
+            };
+
+            Ok(Fixture {
+                name,
+                input,
+                answer,
+                params,
+            })
+        })
+        .collect()
+}
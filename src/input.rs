@@ -0,0 +1,141 @@
+use std::{error::Error, fmt::Display};
+
+use crate::registry;
+
+/// Rejection of a puzzle input that doesn't follow the format described by the puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid input: {}", self.0)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<nom::Err<nom::error::Error<&str>>> for ParseError {
+    fn from(e: nom::Err<nom::error::Error<&str>>) -> Self {
+        match e {
+            nom::Err::Incomplete(_) => Self::new("unexpected end of input"),
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                let line = e.input.lines().next().unwrap_or_default();
+                Self::new(format!("{:?} failed at {:?}", e.code, line))
+            }
+        }
+    }
+}
+
+/// Checks that a model printed back as puzzle text parses to that same model, so a printer can't
+/// silently drop what its parser reads.
+pub fn printed_back<M: PartialEq>(
+    model: &M,
+    reparsed: Result<M, ParseError>,
+) -> Result<(), ParseError> {
+    match reparsed {
+        Ok(reparsed) if reparsed == *model => Ok(()),
+        Ok(_) => Err(ParseError::new("printed back as another model")),
+        Err(e) => Err(ParseError::new(format!("printed back unparsable: {}", e.0))),
+    }
+}
+
+/// Brings puzzle text from any source into the shape the parsers expect: LF line endings, no
+/// trailing spaces on any line and no trailing blank lines, so the last line is not terminated by a
+/// newline. Leading whitespace and blank lines between sections are significant and kept as is.
+pub fn normalize(input: &str) -> String {
+    let mut normalized = String::with_capacity(input.len());
+
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        normalized.push_str(line.trim_end_matches([' ', '\t']));
+        normalized.push('\n');
+    }
+
+    let len = normalized.trim_end_matches('\n').len();
+    normalized.truncate(len);
+    normalized
+}
+
+/// Normalizes a downloaded input, rejecting anything the site may answer with instead of one: an
+/// error page, a login prompt or an input the day's parser doesn't accept.
+pub fn accept_download(day: usize, body: &str) -> Result<String, ParseError> {
+    let input = normalize(body);
+    let start = input.trim_start().to_ascii_lowercase();
+
+    if input.trim().is_empty() {
+        Err(ParseError::new("empty response"))
+    } else if start.starts_with("<!doctype") || start.starts_with("<html") {
+        Err(ParseError::new("HTML page instead of an input"))
+    } else if start.starts_with("puzzle inputs differ by user") {
+        Err(ParseError::new(
+            "not logged in, session key expired or invalid?",
+        ))
+    } else if start.starts_with("please don't repeatedly request this endpoint") {
+        Err(ParseError::new("puzzle not unlocked yet"))
+    } else {
+        match registry::day(day) {
+            Some(registered) => (registered.validate)(&input).map(|_| input),
+            None => Ok(input),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crlf_and_lf_agree() {
+        let lf = "1000\n2000\n\n3000";
+        let crlf = "1000\r\n2000\r\n\r\n3000\r\n";
+        assert_eq!(normalize(lf), lf);
+        assert_eq!(normalize(crlf), lf);
+    }
+
+    #[test]
+    fn trailing_blank_lines_and_spaces() {
+        assert_eq!(normalize("R 4  \nU 4\t\n\n\n"), "R 4\nU 4");
+        assert_eq!(normalize("  \n \n"), "");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn leading_whitespace_kept() {
+        let map = "        ...#\n        .#..\n\n10R5L5R10L4R5L5";
+        assert_eq!(normalize(map), map);
+        assert_eq!(normalize("    [D]    \n[N] [C]    "), "    [D]\n[N] [C]");
+    }
+
+    #[test]
+    fn only_inputs_accepted() {
+        assert_eq!(accept_download(2, "A Y\r\nB X\r\n").unwrap(), "A Y\nB X");
+        assert!(accept_download(2, "").is_err());
+        assert!(accept_download(2, "<!DOCTYPE html>\n<html lang=\"en-us\">").is_err());
+        assert!(accept_download(
+            2,
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n"
+        )
+        .is_err());
+        assert!(accept_download(
+            2,
+            "Please don't repeatedly request this endpoint before it unlocks! \
+             The calendar countdown is synchronized with the server time; \
+             the link will be enabled on the calendar the instant this puzzle becomes available.\n"
+        )
+        .is_err());
+        assert!(accept_download(2, "404 Not Found").is_err());
+    }
+
+    #[test]
+    fn printers_checked_against_the_model() {
+        assert_eq!(printed_back(&vec![1, 2], Ok(vec![1, 2])), Ok(()));
+        assert!(printed_back(&vec![1, 2], Ok(vec![1])).is_err());
+        assert!(printed_back(&vec![1, 2], Err(ParseError::new("no number"))).is_err());
+    }
+}
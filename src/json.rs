@@ -0,0 +1,250 @@
+use std::fmt::{Display, Write};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::{char, multispace0, none_of},
+    combinator::{all_consuming, map_opt, value},
+    multi::{fold_many0, separated_list0},
+    number::complete::double,
+    sequence::{delimited, preceded, separated_pair},
+    IResult, Parser,
+};
+
+use crate::input::ParseError;
+
+/// Just enough JSON for the puzzle site's APIs and our own, objects keep their order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Integral numbers only.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_f64()
+            .filter(|n| n.fract() == 0.0 && n.abs() < 2f64.powi(53))
+            .map(|n| n as i64)
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match self {
+            Value::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Compact JSON.
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Value::Number(_) => f.write_str("null"),
+            Value::String(s) => write_string(f, s),
+            Value::Array(values) => {
+                f.write_char('[')?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(entries) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Value, ParseError> {
+    let (_, value) = all_consuming(delimited(multispace0, p_value, multispace0))(input)?;
+    Ok(value)
+}
+
+fn p_value(input: &str) -> IResult<&str, Value> {
+    alt((
+        value(Value::Null, tag("null")),
+        value(Value::Bool(true), tag("true")),
+        value(Value::Bool(false), tag("false")),
+        double.map(Value::Number),
+        p_string.map(Value::String),
+        p_array.map(Value::Array),
+        p_object.map(Value::Object),
+    ))(input)
+}
+
+fn p_string(input: &str) -> IResult<&str, String> {
+    delimited(
+        char('"'),
+        fold_many0(p_char, String::new, |mut s, c| {
+            s.push(c);
+            s
+        }),
+        char('"'),
+    )(input)
+}
+
+fn p_char(input: &str) -> IResult<&str, char> {
+    alt((
+        none_of("\"\\"),
+        preceded(
+            char('\\'),
+            alt((
+                value('"', char('"')),
+                value('\\', char('\\')),
+                value('/', char('/')),
+                value('\u{8}', char('b')),
+                value('\u{c}', char('f')),
+                value('\n', char('n')),
+                value('\r', char('r')),
+                value('\t', char('t')),
+                // surrogate pairs are left out, the site doesn't send them
+                map_opt(
+                    preceded(
+                        char('u'),
+                        take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+                    ),
+                    |hex| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                ),
+            )),
+        ),
+    ))(input)
+}
+
+fn p_array(input: &str) -> IResult<&str, Vec<Value>> {
+    delimited(
+        char('[').and(multispace0),
+        separated_list0(char(','), delimited(multispace0, p_value, multispace0)),
+        char(']'),
+    )(input)
+}
+
+fn p_object(input: &str) -> IResult<&str, Vec<(String, Value)>> {
+    delimited(
+        char('{').and(multispace0),
+        separated_list0(
+            char(','),
+            separated_pair(
+                delimited(multispace0, p_string, multispace0),
+                char(':'),
+                delimited(multispace0, p_value, multispace0),
+            ),
+        ),
+        char('}'),
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let text = r#"{"a":[1,-2.5,true,null],"b":{"c":"d\"\né"},"e":[]}"#;
+        let value = parse(text).unwrap();
+        assert_eq!(value.get("a").unwrap().as_array().unwrap().len(), 4);
+        assert_eq!(
+            value.get("b").unwrap().get("c").unwrap().as_str(),
+            Some("d\"\né")
+        );
+        assert_eq!(value.to_string(), text);
+        assert_eq!(parse(&value.to_string()).unwrap(), value);
+    }
+
+    #[test]
+    fn whitespace_and_errors() {
+        let value = parse(" { \"n\" : 1669870800 , \"m\" : { } }\n").unwrap();
+        assert_eq!(value.get("n").unwrap().as_i64(), Some(1669870800));
+        assert_eq!(value.get("m").unwrap().as_object(), Some(&[][..]));
+        assert!(parse("{\"a\":}").is_err());
+        assert!(parse("[1,2").is_err());
+        assert!(parse("<!DOCTYPE html>").is_err());
+        assert!(parse("").is_err());
+    }
+}
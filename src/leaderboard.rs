@@ -0,0 +1,298 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Write,
+    fs,
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use crate::{
+    client::Client,
+    input::ParseError,
+    json::{self, Value},
+    unlock::{unlock_time, Countdown},
+};
+
+/// The site asks for private leaderboards not to be fetched more often than this.
+pub const CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaderboard {
+    pub event: String,
+    /// Ordered by local score, best first.
+    pub members: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u64,
+    /// Unix timestamps of the stars earned, by day and part.
+    pub completions: BTreeMap<usize, [Option<u64>; 2]>,
+}
+
+impl Member {
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// Time it took to earn the star of a part since the puzzle unlocked.
+    pub fn completion_time(&self, day: usize, part: usize) -> Option<Duration> {
+        let ts = self.completions.get(&day)?[part]?;
+        Some(
+            (UNIX_EPOCH + Duration::from_secs(ts))
+                .duration_since(unlock_time(day))
+                .unwrap_or_default(),
+        )
+    }
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, ParseError> {
+    value
+        .get(key)
+        .ok_or_else(|| ParseError::new(format!("leaderboard: missing {}", key)))
+}
+
+fn number(value: &Value, key: &str) -> Result<u64, ParseError> {
+    field(value, key)?
+        .as_i64()
+        .and_then(|n| u64::try_from(n).ok())
+        .ok_or_else(|| ParseError::new(format!("leaderboard: {} is not a number", key)))
+}
+
+pub fn parse(text: &str) -> Result<Leaderboard, ParseError> {
+    let root = json::parse(text)?;
+    let event = match field(&root, "event")? {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return Err(ParseError::new("leaderboard: event is not a year")),
+    };
+
+    let mut members = field(&root, "members")?
+        .as_object()
+        .ok_or_else(|| ParseError::new("leaderboard: members is not an object"))?
+        .iter()
+        .map(|(_, m)| p_member(m))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    members.sort_by(|a, b| {
+        (b.local_score, b.stars)
+            .cmp(&(a.local_score, a.stars))
+            .then(a.id.cmp(&b.id))
+    });
+
+    Ok(Leaderboard { event, members })
+}
+
+fn p_member(value: &Value) -> Result<Member, ParseError> {
+    let mut completions = BTreeMap::new();
+    let days = field(value, "completion_day_level")?
+        .as_object()
+        .ok_or_else(|| ParseError::new("leaderboard: completion_day_level is not an object"))?;
+
+    for (day, parts) in days {
+        let day = day
+            .parse::<usize>()
+            .ok()
+            .filter(|d| (1..=25).contains(d))
+            .ok_or_else(|| ParseError::new(format!("leaderboard: no day {}", day)))?;
+
+        let mut stars = [None; 2];
+        for (part, star) in stars.iter_mut().enumerate() {
+            if let Some(level) = parts.get(&(part + 1).to_string()) {
+                *star = Some(number(level, "get_star_ts")?);
+            }
+        }
+        completions.insert(day, stars);
+    }
+
+    Ok(Member {
+        id: number(value, "id")?,
+        name: field(value, "name")?.as_str().map(str::to_owned),
+        stars: number(value, "stars")? as u32,
+        local_score: number(value, "local_score")?,
+        completions,
+    })
+}
+
+/// Reads the leaderboard cached under `cache_dir` if it was fetched less than [`CACHE_TTL`] ago,
+/// fetches and caches it otherwise.
+pub fn load_or_fetch(
+    client: &mut Client,
+    cache_dir: &Path,
+    id: &str,
+) -> Result<Leaderboard, Box<dyn Error>> {
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("invalid leaderboard id {}", id).into());
+    }
+
+    let path = cache_dir.join(format!("leaderboard_{}.json", id));
+    let fresh = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < CACHE_TTL);
+    if fresh {
+        if let Ok(leaderboard) = parse(&fs::read_to_string(&path)?) {
+            return Ok(leaderboard);
+        }
+    }
+
+    let text = client.get(&format!("/leaderboard/private/view/{}.json", id))?;
+    // without access the site redirects to an HTML page, which must not be cached
+    let leaderboard = parse(&text).map_err(|e| {
+        format!(
+            "{}, no access to leaderboard {} or session key expired?",
+            e, id
+        )
+    })?;
+    fs::create_dir_all(cache_dir)?;
+    fs::write(&path, text)?;
+
+    Ok(leaderboard)
+}
+
+/// Members by score with their stars, `*` for both parts and `+` for the first one only, followed
+/// by the completion times of each day since the puzzle unlocked.
+pub fn render(leaderboard: &Leaderboard) -> String {
+    let mut out = String::new();
+    let names: Vec<String> = leaderboard
+        .members
+        .iter()
+        .map(|m| m.display_name())
+        .collect();
+    let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+
+    writeln!(out, "Private leaderboard of {}", leaderboard.event).unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "{:>3}  {:>5}  {:>5}  {:<25}  Name",
+        "#", "Score", "Stars", "Days"
+    )
+    .unwrap();
+    for (rank, (member, name)) in leaderboard.members.iter().zip(&names).enumerate() {
+        let days: String = (1..=25)
+            .map(|day| match member.completions.get(&day) {
+                Some([Some(_), Some(_)]) => '*',
+                Some([Some(_), None]) => '+',
+                _ => '.',
+            })
+            .collect();
+        writeln!(
+            out,
+            "{:>3}  {:>5}  {:>5}  {}  {}",
+            rank + 1,
+            member.local_score,
+            member.stars,
+            days,
+            name
+        )
+        .unwrap();
+    }
+
+    for day in 1..=25 {
+        let mut finishers: Vec<_> = leaderboard
+            .members
+            .iter()
+            .zip(&names)
+            .filter_map(|(member, name)| {
+                let one = member.completion_time(day, 0)?;
+                Some((member.completion_time(day, 1), one, name))
+            })
+            .collect();
+        if finishers.is_empty() {
+            continue;
+        }
+        // both stars first, faster first
+        finishers.sort_by_key(|&(two, one, _)| (two.is_none(), two, one));
+
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "{:<width$}  {:>15}  {:>15}",
+            format!("Day {}", day),
+            "Part 1",
+            "Part 2"
+        )
+        .unwrap();
+        for (two, one, name) in finishers {
+            let two = two.map_or("-".to_owned(), |t| Countdown(t).to_string());
+            writeln!(
+                out,
+                "{:<width$}  {:>15}  {:>15}",
+                name,
+                Countdown(one).to_string(),
+                two
+            )
+            .unwrap();
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNLOCK: u64 = 1_669_870_800;
+
+    fn recorded() -> String {
+        format!(
+            r#"{{"owner_id":1,"event":"2022","members":{{
+"1":{{"id":1,"name":"Alice","stars":3,"local_score":5,"global_score":0,"last_star_ts":0,
+ "completion_day_level":{{"1":{{"1":{{"get_star_ts":{},"star_index":1}},"2":{{"get_star_ts":{},"star_index":2}}}},
+ "2":{{"1":{{"get_star_ts":{},"star_index":3}}}}}}}},
+"2":{{"id":2,"name":null,"stars":2,"local_score":6,"global_score":0,"last_star_ts":0,
+ "completion_day_level":{{"1":{{"1":{{"get_star_ts":{},"star_index":1}},"2":{{"get_star_ts":{},"star_index":2}}}}}}}},
+"3":{{"id":3,"name":"Carol","stars":0,"local_score":0,"global_score":0,"last_star_ts":0,"completion_day_level":{{}}}}
+}}}}"#,
+            UNLOCK + 100,
+            UNLOCK + 200,
+            UNLOCK + 86400 + 3600,
+            UNLOCK + 50,
+            UNLOCK + 90061,
+        )
+    }
+
+    #[test]
+    fn ordered_by_score() {
+        let leaderboard = parse(&recorded()).unwrap();
+        let ids: Vec<u64> = leaderboard.members.iter().map(|m| m.id).collect();
+        assert_eq!(ids, [2, 1, 3]);
+        assert_eq!(leaderboard.members[0].display_name(), "(anonymous user #2)");
+        assert_eq!(
+            leaderboard.members[1].completion_time(2, 0),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(leaderboard.members[1].completion_time(2, 1), None);
+    }
+
+    #[test]
+    fn rendered() {
+        let rendered = render(&parse(&recorded()).unwrap());
+        assert!(
+            rendered.contains("  1      6      2  *........................  (anonymous user #2)")
+        );
+        assert!(rendered.contains("  2      5      3  *+.......................  Alice"));
+        assert!(rendered.contains("  3      0      0  .........................  Carol"));
+        assert!(rendered.contains("Alice                    00h 01m 40s      00h 03m 20s"));
+        assert!(rendered.contains("(anonymous user #2)      00h 00m 50s   1d 01h 01m 01s"));
+        assert!(rendered.contains("Alice                    01h 00m 00s                -"));
+        assert!(!rendered.contains("Day 3"));
+    }
+
+    #[test]
+    fn not_a_leaderboard() {
+        assert!(parse("<!DOCTYPE html>").is_err());
+        assert!(parse(r#"{"event":"2022"}"#).is_err());
+        assert!(parse(r#"{"event":"2022","members":{"1":{"id":1}}}"#).is_err());
+    }
+}
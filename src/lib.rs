@@ -1,3 +1,6 @@
+pub mod answers;
+pub mod checkpoint;
+pub mod client;
 pub mod day_1;
 pub mod day_10;
 pub mod day_11;
@@ -23,44 +26,67 @@ pub mod day_6;
 pub mod day_7;
 pub mod day_8;
 pub mod day_9;
+pub mod ffi;
+pub mod fixtures;
+pub mod input;
+pub mod json;
+pub mod leaderboard;
+pub mod overflow;
+pub mod params;
+pub mod progress;
+pub mod registry;
+pub mod report;
+pub mod scaffold;
+pub mod scale;
+pub mod search;
+pub mod serve;
+pub mod snapshot;
+pub mod timing;
+pub mod unlock;
 pub mod utils;
+pub mod vault;
 
+use client::Client;
+use input::{accept_download, normalize};
 use std::error::Error;
 use std::fs::{self, File};
 use std::io::Read;
 use std::path::Path;
+use std::time::SystemTime;
+use unlock::Countdown;
 
 const SESSION_KEY: &str = include_str!("../SESSION_KEY");
 const ADVENT_URL: &str = "https://adventofcode.com/2022";
 
-pub fn load_or_download(id: usize) -> Result<String, Box<dyn Error>> {
+/// Reads the cached input of a day, decrypting it from the vault or downloading it first if
+/// needed. Before the puzzle unlocks, either fails with a countdown or, with `wait`, sleeps until
+/// it unlocks.
+pub fn load_or_download(id: usize, wait: bool) -> Result<String, Box<dyn Error>> {
     let input_file_path = format!("./inputs/day_{}", id);
 
     if !Path::new(&input_file_path).exists() {
-        println!("Downloading input for day {}...", id);
-
-        let url = format!("{}/day/{}/input", ADVENT_URL, id);
-        let cookie = format!("session={}", SESSION_KEY);
-        let mut input = ureq::get(&url)
-            .set("Cookie", &cookie)
-            .call()
-            .map_err(|e| {
-                eprintln!("Session key expired or invalid?");
-                e
-            })?
-            .into_string()?
-            .into_bytes();
-
-        // for some reason inputs downloaded this way may contain an additional \n at the end
-        if input.last() == Some(&b'\n') {
-            input.truncate(input.len() - 1);
+        if let Some(input) = load_from_vault(id)? {
+            return Ok(normalize(&input));
         }
 
-        if input.starts_with(b"Puzzle inputs differ by user") {
-            return Err("Session key expired or invalid?".into());
+        if let Some(left) = unlock::until_unlock(id, SystemTime::now()) {
+            if !wait {
+                return Err(
+                    format!("day {} unlocks in {}, use --wait", id, Countdown(left)).into(),
+                );
+            }
+            unlock::wait_for_unlock(id);
         }
 
-        fs::write(&input_file_path, &input)?;
+        println!("Downloading input for day {}...", id);
+
+        let input = advent_client()
+            .get(&format!("/day/{}/input", id))
+            .inspect_err(|_| eprintln!("Session key expired or invalid?"))?;
+
+        // nothing but a real input is ever cached
+        let input = accept_download(id, &input)?;
+        fs::write(&input_file_path, input)?;
         println!("Downloaded input for day {}", id);
     }
 
@@ -68,5 +94,30 @@ pub fn load_or_download(id: usize) -> Result<String, Box<dyn Error>> {
     let mut file = File::open(&input_file_path)?;
     file.read_to_string(&mut input)?;
 
-    Ok(input)
+    Ok(normalize(&input))
+}
+
+/// The input of a day from the vault, an error if it is there but the passphrase is missing.
+fn load_from_vault(id: usize) -> Result<Option<String>, Box<dyn Error>> {
+    let name = format!("day_{}", id);
+    if !Path::new(vault::VAULT).join(&name).exists() {
+        return Ok(None);
+    }
+
+    let passphrase = vault::passphrase().ok_or_else(|| {
+        format!(
+            "day {} is in the vault, its passphrase goes in {} or {}",
+            id,
+            vault::PASSPHRASE_FILE,
+            vault::PASSPHRASE_VAR
+        )
+    })?;
+    vault::Vault::new(vault::VAULT, passphrase).read(&name)
+}
+
+/// Client for the puzzle site, throttled across runs and identified by the configured contact.
+pub fn advent_client() -> Client {
+    let contact = client::contact();
+    Client::new(ADVENT_URL, SESSION_KEY, contact.as_deref())
+        .with_throttle(client::INTERVAL, Some(client::STAMP_FILE.into()))
 }
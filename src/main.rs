@@ -1,51 +1,306 @@
-use advent::load_or_download;
+use advent::{
+    advent_client, answers, fixtures, leaderboard, load_or_download,
+    params::Params,
+    progress::{self, Progress, PROGRESS},
+    registry::{self, DEFAULT},
+    report, scaffold, scale, search, serve, timing,
+    vault::{self, Vault},
+};
 use advent_2022 as advent;
 use std::env;
 use std::error::Error;
+use std::fs;
 use std::io::{self, ErrorKind};
+use std::net::TcpListener;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-type Solution = fn(&str) -> String;
+const USAGE: &str = "USAGE:
+    EXEC DAY [--variant NAME] [--example] [--wait] [--record] [--param NAME=VALUE]...
+             [--search-stats] [--check-heuristic]
+    EXEC compare DAY [--wait] [--param NAME=VALUE]...
+    EXEC new DAY
+    EXEC leaderboard ID
+    EXEC report [--output FILE]
+    EXEC scale DAY [--budget SECONDS]
+    EXEC status [--sync]
+    EXEC lock
+    EXEC unlock
+    EXEC serve [--port N]";
+
+const FIXTURES: &str = "./fixtures";
+const INPUTS: &str = "./inputs";
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let solutions: Vec<Solution> = vec![
-        advent::day_1::solution,
-        advent::day_2::solution,
-        advent::day_3::solution,
-        advent::day_4::solution,
-        advent::day_5::solution,
-        advent::day_6::solution,
-        advent::day_7::solution,
-        advent::day_8::solution,
-        advent::day_9::solution,
-        advent::day_10::solution,
-        advent::day_11::solution,
-        advent::day_12::solution,
-        advent::day_13::solution,
-        advent::day_14::solution,
-        advent::day_15::solution,
-        advent::day_16::solution,
-        advent::day_17::solution,
-        advent::day_18::solution,
-        advent::day_19::solution,
-        advent::day_20::solution,
-        advent::day_21::solution,
-        advent::day_22::solution,
-        advent::day_23::solution,
-        advent::day_24::solution,
-        advent::day_25::solution,
-    ];
+    let usage = || io::Error::new(ErrorKind::InvalidInput, USAGE);
+    let parse_day = |arg: Option<String>| arg.and_then(|s| s.parse::<usize>().ok());
 
     let mut args = env::args();
     args.next();
-    let day = args
-        .next()
-        .and_then(|s| s.parse::<usize>().ok())
-        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "USAGE: EXEC DAY"))?;
+    let command = args.next().ok_or_else(usage)?;
+
+    match command.as_str() {
+        "new" => {
+            let day = parse_day(args.next()).ok_or_else(usage)?;
+            scaffold::new_day(Path::new("."), day)?;
+            println!("Created day {}", day);
+        }
+        "leaderboard" => {
+            let id = args.next().ok_or_else(usage)?;
+            let leaderboard =
+                leaderboard::load_or_fetch(&mut advent_client(), Path::new(INPUTS), &id)?;
+            print!("{}", leaderboard::render(&leaderboard));
+        }
+        "serve" => {
+            let port = match args.next().as_deref() {
+                None => 8022,
+                Some("--port") => args
+                    .next()
+                    .and_then(|p| p.parse::<u16>().ok())
+                    .ok_or_else(usage)?,
+                Some(_) => return Err(usage().into()),
+            };
+            let listener = TcpListener::bind(("127.0.0.1", port))?;
+            println!("Serving on http://{}", listener.local_addr()?);
+            serve::serve(listener, serve::Limits::default())?;
+        }
+        "report" => {
+            let output = match args.next().as_deref() {
+                None => "report.html".to_owned(),
+                Some("--output") => args.next().ok_or_else(usage)?,
+                Some(_) => return Err(usage().into()),
+            };
+            report(&output)?;
+        }
+        "status" => {
+            let sync = match args.next().as_deref() {
+                None => false,
+                Some("--sync") => true,
+                Some(_) => return Err(usage().into()),
+            };
+            status(sync)?;
+        }
+        "lock" | "unlock" => {
+            if args.next().is_some() {
+                return Err(usage().into());
+            }
+            let passphrase = vault::passphrase().ok_or_else(|| {
+                format!(
+                    "no passphrase in {} or {}",
+                    vault::PASSPHRASE_FILE,
+                    vault::PASSPHRASE_VAR
+                )
+            })?;
+            let vault = Vault::new(vault::VAULT, passphrase);
+            if command == "lock" {
+                let locked = vault.lock(Path::new(INPUTS))?;
+                println!(
+                    "Locked {} changed inputs into {}",
+                    locked.len(),
+                    vault::VAULT
+                );
+            } else {
+                let unlocked = vault.unlock(Path::new(INPUTS))?;
+                println!("Unlocked {} inputs into {}", unlocked.len(), INPUTS);
+            }
+        }
+        "scale" => {
+            let day = parse_day(args.next()).ok_or_else(usage)?;
+            let budget = match args.next().as_deref() {
+                None => Duration::from_secs(2),
+                Some("--budget") => args
+                    .next()
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .filter(|s| *s > 0.0)
+                    .map(Duration::from_secs_f64)
+                    .ok_or_else(usage)?,
+                Some(_) => return Err(usage().into()),
+            };
+            let samples = scale::measure(find_day(day)?, budget)?;
+            print!("{}", scale::render(day, &samples));
+        }
+        "compare" => {
+            let day = parse_day(args.next()).ok_or_else(usage)?;
+            let mut params = find_day(day)?.params();
+            let mut wait = false;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--wait" => wait = true,
+                    "--param" => params.set(&args.next().ok_or_else(usage)?)?,
+                    _ => return Err(usage().into()),
+                }
+            }
+            compare(day, &params, wait)?;
+        }
+        _ => {
+            let day = parse_day(Some(command)).ok_or_else(usage)?;
+            let mut variant = DEFAULT.to_string();
+            let mut example = false;
+            let mut wait = false;
+            let mut record = false;
+            // none, or whether to check the heuristics of the searches reported
+            let mut searches = None;
+            let mut overrides = vec![];
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--variant" => variant = args.next().ok_or_else(usage)?,
+                    "--example" => example = true,
+                    "--wait" => wait = true,
+                    "--record" => record = true,
+                    "--search-stats" => searches = searches.or(Some(false)),
+                    "--check-heuristic" => searches = Some(true),
+                    "--param" => overrides.push(args.next().ok_or_else(usage)?),
+                    _ => return Err(usage().into()),
+                }
+            }
+
+            if example {
+                run_examples(day, &variant, &overrides)?;
+            } else {
+                let mut params = find_day(day)?.params();
+                for assignment in &overrides {
+                    params.set(assignment)?;
+                }
+                run(day, &variant, &params, wait, record, searches)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn find_day(day: usize) -> Result<&'static registry::Day, String> {
+    registry::day(day).ok_or_else(|| format!("no solution for day {}", day))
+}
 
-    let input = load_or_download(day)?;
-    let answer = solutions[day - 1](&input);
+fn run(
+    day: usize,
+    variant: &str,
+    params: &Params,
+    wait: bool,
+    record: bool,
+    searches: Option<bool>,
+) -> Result<(), Box<dyn Error>> {
+    let solution = find_day(day)?
+        .variant(variant)
+        .ok_or_else(|| format!("no variant named {} for day {}", variant, day))?;
+
+    let input = load_or_download(day, wait)?;
+    let ((answer, reports), timings) = timing::timed(|| match searches {
+        Some(check) => search::instrumented(check, || solution(&input, params)),
+        None => (solution(&input, params), vec![]),
+    });
+    let answer = answer?;
 
     println!("Answer to day {} is: {}", day, answer);
+    println!("Solved in {}", timings);
+    for (i, report) in reports.iter().enumerate() {
+        println!("Search {}: {}", i + 1, report);
+    }
+    if searches.is_some() && reports.is_empty() {
+        println!("No instrumented search in day {}", day);
+    }
+
+    let answers = Path::new(answers::ANSWERS);
+    if record {
+        answers::record(answers, day, &answer)?;
+        let mut progress = Progress::load(Path::new(PROGRESS))?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        progress.solved(day, registry::parts(&answer).len(), now);
+        progress.save(Path::new(PROGRESS))?;
+        println!("Recorded as the accepted answer of day {}", day);
+    } else if answers::check(answers, day, &answer) == Some(false) {
+        println!(
+            "Differs from the accepted answer: {}",
+            answers::expected(answers, day).unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn status(sync: bool) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(PROGRESS);
+    let mut progress = Progress::load(path)?;
+    if sync {
+        progress::sync_stars(&mut advent_client(), &mut progress)?;
+        progress.save(path)?;
+    }
+
+    let answers = Path::new(answers::ANSWERS);
+    print!(
+        "{}",
+        progress::render(
+            &progress,
+            |day| Path::new(INPUTS).join(format!("day_{}", day)).exists(),
+            |day| answers::expected(answers, day).map_or(0, |a| registry::parts(&a).len())
+        )
+    );
 
     Ok(())
 }
+
+fn report(output: &str) -> Result<(), Box<dyn Error>> {
+    let answers = Path::new(answers::ANSWERS);
+    let results = report::run_all(
+        |day| load_or_download(day, false),
+        |day, answer| answers::check(answers, day, answer),
+    );
+    fs::write(output, report::render(&results))?;
+    println!("Report of {} days written to {}", results.len(), output);
+
+    Ok(())
+}
+
+/// Runs the solution on every fixture of the day, with overrides applied on top of the fixture's
+/// own parameters.
+fn run_examples(day: usize, variant: &str, overrides: &[String]) -> Result<(), Box<dyn Error>> {
+    let registered = find_day(day)?;
+    let solution = registered
+        .variant(variant)
+        .ok_or_else(|| format!("no variant named {} for day {}", variant, day))?;
+
+    for fixture in fixtures::load(Path::new(FIXTURES), day)? {
+        let mut params = fixture.params(registered)?;
+        for assignment in overrides {
+            params.set(assignment)?;
+        }
+
+        let answer = solution(&fixture.input, &params)?;
+        println!("Answer to day {} {} is: {}", day, fixture.name, answer);
+        if !fixture.expects(&answer) {
+            println!("Expected: {}", fixture.answer);
+        }
+    }
+
+    Ok(())
+}
+
+fn compare(day: usize, params: &Params, wait: bool) -> Result<(), Box<dyn Error>> {
+    let variants: Vec<_> = find_day(day)?.all_variants().collect();
+    let input = load_or_download(day, wait)?;
+
+    let mut answers = vec![];
+    for (name, solution) in variants {
+        let (answer, timings) = timing::timed(|| solution(&input, params));
+        let answer = answer?;
+
+        println!("{:<16} {:>12.3?}  {}", name, timings.total, answer);
+        if !timings.phases.is_empty() {
+            let phases: Vec<String> = timings
+                .phases
+                .iter()
+                .map(|(phase, time)| format!("{} {:.3?}", phase, time))
+                .collect();
+            println!("{:<16} {}", "", phases.join(", "));
+        }
+        answers.push(answer);
+    }
+
+    if answers.windows(2).all(|w| w[0] == w[1]) {
+        println!("All {} variants of day {} agree", answers.len(), day);
+        Ok(())
+    } else {
+        Err(format!("variants of day {} disagree", day).into())
+    }
+}
@@ -0,0 +1,171 @@
+//! Arithmetic on values that grow with the input. Built with the `checked` feature every operation
+//! is checked and an overflow is an error naming the day and the operation, otherwise they are the
+//! plain operators, which wrap silently in release builds. Division is always checked.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    ops::{Add, Div, Mul, Sub},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub day: usize,
+    pub operation: &'static str,
+    pub expression: String,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}: overflow in {} computing {}",
+            self.day, self.operation, self.expression
+        )
+    }
+}
+
+impl Error for Overflow {}
+
+pub trait Checked:
+    Copy + Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    const ZERO: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! checked {
+    ($($t:ty),*) => {$(
+        impl Checked for $t {
+            const ZERO: Self = 0;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+        }
+    )*};
+}
+
+checked!(i32, i64, isize);
+
+/// The operations of one place of a solution, named in the overflow errors.
+#[derive(Debug, Clone, Copy)]
+pub struct Arith {
+    day: usize,
+    operation: &'static str,
+}
+
+impl Arith {
+    pub const fn new(day: usize, operation: &'static str) -> Self {
+        Self { day, operation }
+    }
+
+    fn overflow(self, expression: String) -> Overflow {
+        Overflow {
+            day: self.day,
+            operation: self.operation,
+            expression,
+        }
+    }
+
+    fn check<T: Checked>(
+        self,
+        checked: Option<T>,
+        unchecked: impl FnOnce() -> T,
+        expression: impl FnOnce() -> String,
+    ) -> Result<T, Overflow> {
+        if cfg!(feature = "checked") {
+            checked.ok_or_else(|| self.overflow(expression()))
+        } else {
+            Ok(unchecked())
+        }
+    }
+
+    pub fn add<T: Checked>(self, lhs: T, rhs: T) -> Result<T, Overflow> {
+        self.check(
+            lhs.checked_add(rhs),
+            || lhs + rhs,
+            || format!("{} + {}", lhs, rhs),
+        )
+    }
+
+    pub fn sub<T: Checked>(self, lhs: T, rhs: T) -> Result<T, Overflow> {
+        self.check(
+            lhs.checked_sub(rhs),
+            || lhs - rhs,
+            || format!("{} - {}", lhs, rhs),
+        )
+    }
+
+    pub fn mul<T: Checked>(self, lhs: T, rhs: T) -> Result<T, Overflow> {
+        self.check(
+            lhs.checked_mul(rhs),
+            || lhs * rhs,
+            || format!("{} * {}", lhs, rhs),
+        )
+    }
+
+    /// Division by zero is reported as an overflow too. The plain operator panics on it even in
+    /// release builds, so division is checked with or without the feature.
+    pub fn div<T: Checked>(self, lhs: T, rhs: T) -> Result<T, Overflow> {
+        lhs.checked_div(rhs)
+            .ok_or_else(|| self.overflow(format!("{} / {}", lhs, rhs)))
+    }
+
+    pub fn sum<T: Checked>(self, items: impl IntoIterator<Item = T>) -> Result<T, Overflow> {
+        items
+            .into_iter()
+            .try_fold(T::ZERO, |sum, item| self.add(sum, item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST: Arith = Arith::new(0, "test");
+
+    #[test]
+    fn in_range() {
+        assert_eq!(TEST.add(2i64, 3), Ok(5));
+        assert_eq!(TEST.sub(2isize, 3), Ok(-1));
+        assert_eq!(TEST.mul(-4i32, 3), Ok(-12));
+        assert_eq!(TEST.div(7i64, 2), Ok(3));
+        assert_eq!(TEST.sum([i64::MAX, -1, 1]), Ok(i64::MAX));
+    }
+
+    #[test]
+    fn division_by_zero_reported() {
+        assert!(TEST.div(1i64, 0).is_err());
+        assert!(TEST.div(i32::MIN, -1).is_err());
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn overflow_named() {
+        let overflow = TEST.mul(i64::MAX / 2, 3).unwrap_err();
+        assert_eq!(
+            overflow.to_string(),
+            format!("day 0: overflow in test computing {} * 3", i64::MAX / 2)
+        );
+        assert!(TEST.sum([i64::MAX, 1, -1]).is_err());
+        assert!(TEST.div(1i32, 0).is_err());
+        assert!(TEST.sub(i32::MIN, 1).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{collections::HashMap, fmt::Display};
+
+/// A constant from the puzzle text that can be overridden without editing the solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: i64,
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Param {
+    pub const fn new(name: &'static str, default: i64) -> Self {
+        Self {
+            name,
+            default,
+            min: i64::MIN,
+            max: i64::MAX,
+        }
+    }
+
+    /// Rejects overrides below `min`, for counts and sizes that make no sense when negative.
+    pub const fn at_least(self, min: i64) -> Self {
+        Self { min, ..self }
+    }
+
+    /// Rejects overrides above `max`, for values the solution keeps in a narrower type.
+    pub const fn at_most(self, max: i64) -> Self {
+        Self { max, ..self }
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (default {})", self.name, self.default)
+    }
+}
+
+/// Values of the parameters declared by a day, the defaults unless overridden.
+#[derive(Debug, Clone)]
+pub struct Params {
+    declared: &'static [Param],
+    overrides: HashMap<&'static str, i64>,
+}
+
+impl Params {
+    pub fn new(declared: &'static [Param]) -> Self {
+        Self {
+            declared,
+            overrides: HashMap::new(),
+        }
+    }
+
+    fn declared(&self, name: &str) -> Option<&'static Param> {
+        self.declared.iter().find(|p| p.name == name)
+    }
+
+    /// # Panics
+    ///
+    /// When `name` is not declared by the day, which is a bug in the solution.
+    pub fn get(&self, name: &str) -> i64 {
+        let param = self
+            .declared(name)
+            .unwrap_or_else(|| panic!("undeclared parameter {}", name));
+        self.overrides.get(name).copied().unwrap_or(param.default)
+    }
+
+    /// Overrides a parameter from an assignment in the form of `name=value`.
+    pub fn set(&mut self, assignment: &str) -> Result<(), String> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("expected NAME=VALUE, got {}", assignment))?;
+
+        let param = self.declared(name).ok_or_else(|| {
+            let available: Vec<String> = self.declared.iter().map(|p| p.to_string()).collect();
+            if available.is_empty() {
+                format!("unknown parameter {}, this day has none", name)
+            } else {
+                format!(
+                    "unknown parameter {}, available: {}",
+                    name,
+                    available.join(", ")
+                )
+            }
+        })?;
+
+        let value = value
+            .replace('_', "")
+            .parse::<i64>()
+            .map_err(|_| format!("invalid value for {}: {}", name, value))?;
+        if value < param.min {
+            return Err(format!("{} must be at least {}", name, param.min));
+        }
+        if value > param.max {
+            return Err(format!("{} must be at most {}", name, param.max));
+        }
+
+        self.overrides.insert(param.name, value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param::new("row", 2_000_000),
+        Param::new("rounds", 20)
+            .at_least(0)
+            .at_most(u32::MAX as i64),
+    ];
+
+    #[test]
+    fn defaults_until_overridden() {
+        let mut params = Params::new(PARAMS);
+        assert_eq!(params.get("row"), 2_000_000);
+        params.set("row=10").unwrap();
+        assert_eq!(params.get("row"), 10);
+        params.set("row=-4_000").unwrap();
+        assert_eq!(params.get("row"), -4000);
+        assert_eq!(params.get("rounds"), 20);
+    }
+
+    #[test]
+    fn invalid_overrides() {
+        let mut params = Params::new(PARAMS);
+        assert!(params.set("row").is_err());
+        assert!(params.set("column=1").is_err());
+        assert!(params.set("row=ten").is_err());
+        assert!(params.set("rounds=-1").is_err());
+        assert!(params.set("rounds=4294967296").is_err());
+        assert!(Params::new(&[]).set("row=1").is_err());
+        assert_eq!(params.get("rounds"), 20);
+    }
+
+    #[test]
+    #[should_panic]
+    fn undeclared_parameter() {
+        Params::new(PARAMS).get("column");
+    }
+}
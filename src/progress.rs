@@ -0,0 +1,269 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    fs, io,
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use regex::Regex;
+
+use crate::{
+    client::Client,
+    input::ParseError,
+    unlock::{unlock_time, Countdown},
+};
+
+/// Stars and first solve times of every day, one line per day.
+pub const PROGRESS: &str = "./progress";
+
+const DAYS: usize = 25;
+const HEADER: &str = "# day, stars, first solve of each part in unix time or - if unknown";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DayProgress {
+    pub stars: u8,
+    pub solved: [Option<u64>; 2],
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Progress {
+    days: [DayProgress; DAYS],
+}
+
+impl Progress {
+    /// The saved progress, or none at all if nothing was saved yet.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(parse(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = format!("{}\n", HEADER);
+        for (i, day) in self.days.iter().enumerate() {
+            let time = |t: Option<u64>| t.map_or("-".to_owned(), |t| t.to_string());
+            writeln!(
+                text,
+                "{} {} {} {}",
+                i + 1,
+                day.stars,
+                time(day.solved[0]),
+                time(day.solved[1])
+            )
+            .unwrap();
+        }
+        fs::write(path, text)
+    }
+
+    pub fn day(&self, day: usize) -> &DayProgress {
+        &self.days[day - 1]
+    }
+
+    /// Records the first `parts` parts of a day as solved at `at`, parts solved before keep their
+    /// first time.
+    pub fn solved(&mut self, day: usize, parts: usize, at: u64) {
+        for solved in self.days[day - 1].solved.iter_mut().take(parts) {
+            solved.get_or_insert(at);
+        }
+    }
+
+    /// Stars are never taken back, a page showing fewer is stale or logged out.
+    pub fn earned(&mut self, day: usize, stars: u8) {
+        let day = &mut self.days[day - 1];
+        day.stars = day.stars.max(stars.min(2));
+    }
+
+    pub fn stars(&self) -> u32 {
+        self.days.iter().map(|d| d.stars as u32).sum()
+    }
+}
+
+fn parse(text: &str) -> Result<Progress, ParseError> {
+    let mut progress = Progress::default();
+
+    for line in text
+        .lines()
+        .filter(|l| !l.starts_with('#') && !l.is_empty())
+    {
+        let invalid = || ParseError::new(format!("progress: {:?}", line));
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [day, stars, one, two] = fields[..] else {
+            return Err(invalid());
+        };
+
+        let day = day
+            .parse::<usize>()
+            .ok()
+            .filter(|d| (1..=DAYS).contains(d))
+            .ok_or_else(invalid)?;
+        let time = |t: &str| match t {
+            "-" => Ok(None),
+            t => t.parse().map(Some).map_err(|_| invalid()),
+        };
+        progress.days[day - 1] = DayProgress {
+            stars: stars.parse().ok().filter(|s| *s <= 2).ok_or_else(invalid)?,
+            solved: [time(one)?, time(two)?],
+        };
+    }
+
+    Ok(progress)
+}
+
+/// Stars of every day shown on the event's calendar page.
+pub fn parse_calendar(html: &str) -> Result<Vec<(usize, u8)>, ParseError> {
+    let day = Regex::new(r#"class="calendar-day(\d+)([^"]*)""#).unwrap();
+    let stars: Vec<(usize, u8)> = day
+        .captures_iter(html)
+        .filter_map(|c| {
+            let day = c[1].parse().ok().filter(|d| (1..=DAYS).contains(d))?;
+            let stars = if c[2].contains("calendar-verycomplete") {
+                2
+            } else if c[2].contains("calendar-complete") {
+                1
+            } else {
+                0
+            };
+            Some((day, stars))
+        })
+        .collect();
+
+    if stars.is_empty() {
+        Err(ParseError::new("no calendar in the event page"))
+    } else {
+        Ok(stars)
+    }
+}
+
+/// Updates the stars from the calendar of the event the client points to.
+pub fn sync_stars(client: &mut Client, progress: &mut Progress) -> Result<(), Box<dyn Error>> {
+    for (day, stars) in parse_calendar(&client.get("")?)? {
+        progress.earned(day, stars);
+    }
+    Ok(())
+}
+
+/// What each day is waiting for: `has_input` tells if its input is cached, `verified` how many of
+/// its parts have an accepted answer recorded.
+pub fn render(
+    progress: &Progress,
+    has_input: impl Fn(usize) -> bool,
+    verified: impl Fn(usize) -> usize,
+) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>3}  {:<5}  {:<5}  {:<26}  Part 2",
+        "Day", "Input", "Stars", "Part 1"
+    )
+    .unwrap();
+
+    for day in 1..=DAYS {
+        let status = progress.day(day);
+        let stars = match status.stars {
+            0 => ".".to_owned(),
+            n => "*".repeat(n as usize),
+        };
+        let part = |part: usize| {
+            let verified = if verified(day) > part {
+                "verified"
+            } else {
+                "-"
+            };
+            match status.solved[part] {
+                Some(at) => {
+                    let since_unlock = (UNIX_EPOCH + Duration::from_secs(at))
+                        .duration_since(unlock_time(day))
+                        .unwrap_or_default();
+                    format!(
+                        "{:<8}  {:>16}",
+                        verified,
+                        Countdown(since_unlock).to_string()
+                    )
+                }
+                None => verified.to_owned(),
+            }
+        };
+
+        writeln!(
+            out,
+            "{:>3}  {:<5}  {:<5}  {:<26}  {}",
+            day,
+            if has_input(day) { "yes" } else { "no" },
+            stars,
+            part(0),
+            part(1)
+        )
+        .unwrap();
+    }
+
+    let left = (1..=DAYS).filter(|d| progress.day(*d).stars < 2).count();
+    writeln!(
+        out,
+        "\n{}/{} stars, {} days left",
+        progress.stars(),
+        2 * DAYS,
+        left
+    )
+    .unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNLOCK: u64 = 1_669_870_800;
+
+    #[test]
+    fn saved_and_loaded() {
+        let path = std::env::temp_dir().join(format!("advent-progress-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut progress = Progress::load(&path).unwrap();
+        assert_eq!(progress, Progress::default());
+
+        progress.solved(1, 1, UNLOCK + 60);
+        progress.solved(1, 2, UNLOCK + 120);
+        progress.earned(1, 2);
+        progress.earned(1, 1);
+        progress.earned(3, 1);
+        progress.save(&path).unwrap();
+
+        let loaded = Progress::load(&path).unwrap();
+        assert_eq!(loaded, progress);
+        assert_eq!(
+            *loaded.day(1),
+            DayProgress {
+                stars: 2,
+                solved: [Some(UNLOCK + 60), Some(UNLOCK + 120)]
+            }
+        );
+        assert_eq!(loaded.stars(), 3);
+
+        fs::write(&path, "1 3 - -\n").unwrap();
+        assert!(Progress::load(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rendered() {
+        let mut progress = Progress::default();
+        progress.solved(1, 2, UNLOCK + 3661);
+        progress.earned(1, 2);
+        progress.earned(2, 1);
+
+        let status = render(
+            &progress,
+            |day| day <= 2,
+            |day| if day == 1 { 2 } else { 0 },
+        );
+        assert!(status.contains(
+            "  1  yes    **     verified       01h 01m 01s  verified       01h 01m 01s\n"
+        ));
+        assert!(status.contains("  2  yes    *      -                           -\n"));
+        assert!(status.contains(" 25  no     .      -                           -\n"));
+        assert!(status.ends_with("3/50 stars, 24 days left\n"));
+    }
+}
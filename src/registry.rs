@@ -0,0 +1,259 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::{
+    input::ParseError,
+    overflow::Overflow,
+    params::{Param, Params},
+    utils::Rng,
+    *,
+};
+
+/// Why a solution has no answer: an input it rejects, or a valid one its arithmetic overflows on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Overflow(Overflow),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(e) => e.fmt(f),
+            SolveError::Overflow(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Parse(e) => Some(e),
+            SolveError::Overflow(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(e: ParseError) -> Self {
+        SolveError::Parse(e)
+    }
+}
+
+impl From<Overflow> for SolveError {
+    fn from(e: Overflow) -> Self {
+        SolveError::Overflow(e)
+    }
+}
+
+pub type Solution = fn(&str, &Params) -> Result<String, SolveError>;
+
+/// Parses an input without solving it, to reject malformed inputs early.
+pub type Validate = fn(&str) -> Result<(), ParseError>;
+
+/// Parses an input and prints its model back as puzzle text, an error unless that text parses back
+/// to the same model.
+pub type Print = fn(&str) -> Result<String, ParseError>;
+
+/// An alternative implementation of a day's solution, selected by name.
+pub type Variant = (&'static str, Solution);
+
+/// Builds a valid input of about the given size, for timing a day on growing inputs.
+pub type Generate = fn(usize, &mut Rng) -> String;
+
+/// Draws the state a day reaches on an input, checked against the fixtures' snapshots.
+pub type Render = fn(&str, &Params) -> Result<String, SolveError>;
+
+/// A named rendering of a day, see [`crate::snapshot`].
+pub type Rendering = (&'static str, Render);
+
+/// Name of the solution a day is registered with.
+pub const DEFAULT: &str = "default";
+
+pub struct Day {
+    pub day: usize,
+    pub solution: Solution,
+    pub validate: Validate,
+    pub variants: &'static [Variant],
+    pub params: &'static [Param],
+    pub generate: Option<Generate>,
+    pub renderings: &'static [Rendering],
+    pub print: Option<Print>,
+}
+
+impl Day {
+    const fn new(day: usize, solution: Solution, validate: Validate) -> Self {
+        Self {
+            day,
+            solution,
+            validate,
+            variants: &[],
+            params: &[],
+            generate: None,
+            renderings: &[],
+            print: None,
+        }
+    }
+
+    const fn with_variants(self, variants: &'static [Variant]) -> Self {
+        Self { variants, ..self }
+    }
+
+    const fn with_params(self, params: &'static [Param]) -> Self {
+        Self { params, ..self }
+    }
+
+    const fn with_generator(self, generate: Generate) -> Self {
+        Self {
+            generate: Some(generate),
+            ..self
+        }
+    }
+
+    const fn with_renderings(self, renderings: &'static [Rendering]) -> Self {
+        Self { renderings, ..self }
+    }
+
+    const fn with_printer(self, print: Print) -> Self {
+        Self {
+            print: Some(print),
+            ..self
+        }
+    }
+
+    /// The day's parameters at their defaults, ready to be overridden.
+    pub fn params(&self) -> Params {
+        Params::new(self.params)
+    }
+
+    /// All implementations of the day, the default one first.
+    pub fn all_variants(&self) -> impl Iterator<Item = Variant> + '_ {
+        [(DEFAULT, self.solution)]
+            .into_iter()
+            .chain(self.variants.iter().copied())
+    }
+
+    pub fn variant(&self, name: &str) -> Option<Solution> {
+        self.all_variants()
+            .find(|(n, _)| *n == name)
+            .map(|(_, solution)| solution)
+    }
+}
+
+pub const DAYS: &[Day] = &[
+    Day::new(1, day_1::solution, day_1::validate)
+        .with_variants(&[("sorting", day_1::solution_sorting)])
+        .with_generator(day_1::generate)
+        .with_printer(day_1::reprint),
+    Day::new(2, day_2::solution, day_2::validate)
+        .with_generator(day_2::generate)
+        .with_printer(day_2::reprint),
+    Day::new(3, day_3::solution, day_3::validate)
+        .with_generator(day_3::generate)
+        .with_printer(day_3::reprint),
+    Day::new(4, day_4::solution, day_4::validate)
+        .with_generator(day_4::generate)
+        .with_printer(day_4::reprint),
+    Day::new(5, day_5::solution, day_5::validate)
+        .with_generator(day_5::generate)
+        .with_printer(day_5::reprint),
+    Day::new(6, day_6::solution, day_6::validate)
+        .with_generator(day_6::generate)
+        .with_printer(day_6::reprint),
+    Day::new(7, day_7::solution, day_7::validate)
+        .with_params(day_7::PARAMS)
+        .with_generator(day_7::generate)
+        .with_printer(day_7::reprint),
+    Day::new(8, day_8::solution, day_8::validate)
+        .with_generator(day_8::generate)
+        .with_printer(day_8::reprint),
+    Day::new(9, day_9::solution, day_9::validate)
+        .with_variants(&[("siphash", day_9::solution_siphash)])
+        .with_generator(day_9::generate)
+        .with_printer(day_9::reprint),
+    Day::new(10, day_10::solution, day_10::validate)
+        .with_renderings(&[("crt", day_10::render_crt)])
+        .with_printer(day_10::reprint),
+    Day::new(11, day_11::solution, day_11::validate)
+        .with_params(day_11::PARAMS)
+        .with_printer(day_11::reprint),
+    Day::new(12, day_12::solution, day_12::validate).with_printer(day_12::reprint),
+    Day::new(13, day_13::solution, day_13::validate)
+        .with_generator(day_13::generate)
+        .with_printer(day_13::reprint),
+    Day::new(14, day_14::solution, day_14::validate)
+        .with_variants(&[
+            ("grain-by-grain", day_14::solution_grain_by_grain),
+            ("siphash", day_14::solution_siphash),
+        ])
+        .with_printer(day_14::reprint),
+    Day::new(15, day_15::solution, day_15::validate)
+        .with_params(day_15::PARAMS)
+        .with_printer(day_15::reprint),
+    Day::new(16, day_16::solution, day_16::validate)
+        .with_params(day_16::PARAMS)
+        .with_generator(day_16::generate)
+        .with_printer(day_16::reprint),
+    Day::new(17, day_17::solution, day_17::validate)
+        .with_variants(&[("siphash", day_17::solution_siphash)])
+        .with_params(day_17::PARAMS)
+        .with_renderings(&[("chamber", day_17::render_chamber)])
+        .with_printer(day_17::reprint),
+    Day::new(18, day_18::solution, day_18::validate)
+        .with_generator(day_18::generate)
+        .with_printer(day_18::reprint),
+    Day::new(19, day_19::solution, day_19::validate)
+        .with_params(day_19::PARAMS)
+        .with_generator(day_19::generate)
+        .with_printer(day_19::reprint),
+    Day::new(20, day_20::solution, day_20::validate)
+        .with_params(day_20::PARAMS)
+        .with_generator(day_20::generate)
+        .with_printer(day_20::reprint),
+    Day::new(21, day_21::solution, day_21::validate)
+        .with_generator(day_21::generate)
+        .with_printer(day_21::reprint),
+    Day::new(22, day_22::solution, day_22::validate)
+        .with_params(day_22::PARAMS)
+        .with_generator(day_22::generate)
+        .with_printer(day_22::reprint),
+    Day::new(23, day_23::solution, day_23::validate)
+        .with_variants(&[("siphash", day_23::solution_siphash)])
+        .with_params(day_23::PARAMS)
+        .with_renderings(&[("grove", day_23::render_grove)])
+        .with_printer(day_23::reprint),
+    Day::new(24, day_24::solution, day_24::validate)
+        .with_variants(&[("dijkstra", day_24::solution_dijkstra)])
+        .with_generator(day_24::generate)
+        .with_printer(day_24::reprint),
+    Day::new(25, day_25::solution, day_25::validate)
+        .with_generator(day_25::generate)
+        .with_printer(day_25::reprint),
+];
+
+/// Splits an answer into the answers of its parts. Solutions join them with `", "`, or with a
+/// newline when the last part is a multi-line drawing. Day 25 has a single part.
+pub fn parts(answer: &str) -> Vec<&str> {
+    match answer.split_once('\n') {
+        Some((one, two)) => vec![one, two],
+        None => answer.split(", ").collect(),
+    }
+}
+
+pub fn day(day: usize) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_split() {
+        assert_eq!(parts("24000, 45000"), ["24000", "45000"]);
+        assert_eq!(parts("13140\n##..\n..##\n"), ["13140", "##..\n..##\n"]);
+        assert_eq!(parts("2=-1=0"), ["2=-1=0"]);
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    panic,
+    time::{Duration, Instant},
+};
+
+use crate::registry::DAYS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    /// No accepted answer recorded to compare with.
+    Unknown,
+    /// The day didn't produce an answer at all.
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct DayResult {
+    pub day: usize,
+    /// The answer, or why there is none.
+    pub answer: Result<String, String>,
+    pub parse: Duration,
+    pub solve: Duration,
+    pub verdict: Verdict,
+}
+
+/// Runs every registered day with default parameters on the input given by `load`, checking the
+/// answers with `check`. Parsing is timed on its own with the day's validation, solving is the rest
+/// of the time the solution took.
+pub fn run_all(
+    load: impl Fn(usize) -> Result<String, Box<dyn Error>>,
+    check: impl Fn(usize, &str) -> Option<bool>,
+) -> Vec<DayResult> {
+    DAYS.iter()
+        .map(|day| {
+            let mut result = DayResult {
+                day: day.day,
+                answer: Err(String::new()),
+                parse: Duration::ZERO,
+                solve: Duration::ZERO,
+                verdict: Verdict::Error,
+            };
+
+            let input = match load(day.day) {
+                Ok(input) => input,
+                Err(e) => {
+                    result.answer = Err(e.to_string());
+                    return result;
+                }
+            };
+
+            let start = Instant::now();
+            let parsed = (day.validate)(&input);
+            result.parse = start.elapsed();
+            if let Err(e) = parsed {
+                result.answer = Err(e.to_string());
+                return result;
+            }
+
+            let params = day.params();
+            let start = Instant::now();
+            // one broken day shouldn't take the rest of the report down with it
+            let answer = panic::catch_unwind(|| (day.solution)(&input, &params));
+            result.solve = start.elapsed().saturating_sub(result.parse);
+
+            match answer {
+                Ok(Ok(answer)) => {
+                    result.verdict = match check(day.day, &answer) {
+                        Some(true) => Verdict::Pass,
+                        Some(false) => Verdict::Fail,
+                        None => Verdict::Unknown,
+                    };
+                    result.answer = Ok(answer);
+                }
+                Ok(Err(e)) => result.answer = Err(e.to_string()),
+                Err(_) => result.answer = Err("panicked".to_owned()),
+            }
+
+            result
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.3em 0.8em; text-align: left; vertical-align: top; border-bottom: 1px solid #ddd; }
+pre, code { font-family: monospace; }
+pre { margin: 0; line-height: 1; }
+.bar { display: flex; width: 20em; height: 1em; background: #eee; }
+.parse { background: #6a9fb5; }
+.solve { background: #e5a04b; }
+.pass { color: #2a7a2a; }
+.fail { color: #b22; font-weight: bold; }
+.unknown { color: #888; }
+.error { color: #b22; }
+";
+
+/// A self-contained HTML page of the results, without any external resource.
+pub fn render(results: &[DayResult]) -> String {
+    let longest = results
+        .iter()
+        .map(|r| r.parse + r.solve)
+        .max()
+        .unwrap_or_default()
+        .max(Duration::from_nanos(1));
+    let percent = |d: Duration| 100.0 * d.as_secs_f64() / longest.as_secs_f64();
+    let count = |v: Verdict| results.iter().filter(|r| r.verdict == v).count();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Advent of Code 2022</title>\n");
+    writeln!(html, "<style>{}</style>", STYLE).unwrap();
+    html.push_str("</head>\n<body>\n<h1>Advent of Code 2022</h1>\n");
+    writeln!(
+        html,
+        "<p>{} passed, {} failed, {} unchecked, {} errors. Total time {:.3?}.</p>",
+        count(Verdict::Pass),
+        count(Verdict::Fail),
+        count(Verdict::Unknown),
+        count(Verdict::Error),
+        results.iter().map(|r| r.parse + r.solve).sum::<Duration>()
+    )
+    .unwrap();
+    html.push_str(
+        "<table>\n<tr><th>Day</th><th>Answer</th><th>Result</th><th>Parse</th><th>Solve</th>\
+         <th>Time <span class=\"parse\">&nbsp;parse&nbsp;</span> \
+         <span class=\"solve\">&nbsp;solve&nbsp;</span></th></tr>\n",
+    );
+
+    for result in results {
+        let answer = match &result.answer {
+            // ASCII art only reads right in a monospace block
+            Ok(answer) if answer.contains('\n') => {
+                format!("<pre>{}</pre>", escape(answer.trim_end()))
+            }
+            Ok(answer) => format!("<code>{}</code>", escape(answer)),
+            Err(e) => format!("<span class=\"error\">{}</span>", escape(e)),
+        };
+        let (class, verdict) = match result.verdict {
+            Verdict::Pass => ("pass", "pass"),
+            Verdict::Fail => ("fail", "FAIL"),
+            Verdict::Unknown => ("unknown", "unchecked"),
+            Verdict::Error => ("error", "error"),
+        };
+
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{:.3?}</td><td>{:.3?}</td>\
+             <td><div class=\"bar\"><div class=\"parse\" style=\"width: {:.2}%\"></div>\
+             <div class=\"solve\" style=\"width: {:.2}%\"></div></div></td></tr>",
+            result.day,
+            answer,
+            class,
+            verdict,
+            result.parse,
+            result.solve,
+            percent(result.parse),
+            percent(result.solve),
+        )
+        .unwrap();
+    }
+
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(day: usize, answer: Result<&str, &str>, verdict: Verdict, millis: u64) -> DayResult {
+        DayResult {
+            day,
+            answer: answer.map(str::to_owned).map_err(str::to_owned),
+            parse: Duration::from_millis(millis),
+            solve: Duration::from_millis(3 * millis),
+            verdict,
+        }
+    }
+
+    #[test]
+    fn rendered_self_contained() {
+        let html = render(&[
+            result(1, Ok("24000, 45000"), Verdict::Pass, 1),
+            result(10, Ok("13140\n##..\n#<>.\n"), Verdict::Fail, 2),
+            result(11, Err("invalid input: <bad>"), Verdict::Error, 0),
+        ]);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("http"));
+        assert!(html.contains("<code>24000, 45000</code>"));
+        assert!(html.contains("<pre>13140\n##..\n#&lt;&gt;.</pre>"));
+        assert!(html.contains("invalid input: &lt;bad&gt;"));
+        assert!(html.contains("1 passed, 1 failed, 0 unchecked, 1 errors"));
+        // the slowest day spans the whole bar
+        assert!(html.contains("width: 25.00%\"></div><div class=\"solve\" style=\"width: 75.00%"));
+    }
+
+    #[test]
+    fn every_day_run() {
+        let results = run_all(
+            |day| match day {
+                1 => Ok("1000\n2000\n\n3000".to_owned()),
+                2 => Ok("A Q".to_owned()),
+                _ => Err("no input".into()),
+            },
+            |day, answer| (day == 1).then(|| answer == "3000, 6000"),
+        );
+
+        assert_eq!(results.len(), DAYS.len());
+        assert_eq!(results[0].answer, Ok("3000, 6000".to_owned()));
+        assert_eq!(results[0].verdict, Verdict::Pass);
+        assert_eq!(results[1].verdict, Verdict::Error);
+        assert!(results[1]
+            .answer
+            .as_ref()
+            .unwrap_err()
+            .contains("invalid input"));
+        assert_eq!(results[2].answer, Err("no input".to_owned()));
+    }
+}
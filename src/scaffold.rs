@@ -0,0 +1,250 @@
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+const TEMPLATE: &str = r#"use crate::{input::ParseError, params::Params, registry::SolveError};
+
+pub fn solution(input: &str, _params: &Params) -> Result<String, SolveError> {
+    let lines = parse(input)?;
+    Ok(format!("{}, {}", part_one(&lines), part_two(&lines)))
+}
+
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(drop)
+}
+
+pub fn reprint(input: &str) -> Result<String, ParseError> {
+    parse(input).map(|lines| print(&lines))
+}
+
+fn parse(input: &str) -> Result<Vec<&str>, ParseError> {
+    Ok(input.lines().collect())
+}
+
+fn print(lines: &[&str]) -> String {
+    lines.join("\n")
+}
+
+fn part_one(lines: &[&str]) -> usize {
+    lines.len()
+}
+
+fn part_two(_lines: &[&str]) -> usize {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "";
+
+    #[test]
+    #[ignore = "example not filled in yet"]
+    fn example_part_one() {
+        let lines = parse(INPUT).unwrap();
+        assert_eq!(part_one(&lines), 0);
+    }
+
+    #[test]
+    #[ignore = "example not filled in yet"]
+    fn example_part_two() {
+        let lines = parse(INPUT).unwrap();
+        assert_eq!(part_two(&lines), 0);
+    }
+}
+"#;
+
+/// The answer of the template to its empty example, so that a new day passes the fixture tests
+/// until the real example is filled in.
+const TEMPLATE_ANSWER: &str = "0, 0\n";
+
+/// Creates `src/day_N.rs` under `root` from the template with an empty example in
+/// `fixtures/day_N/`, and registers it in both `src/lib.rs` and `src/registry.rs`. Existing days
+/// are never overwritten.
+pub fn new_day(root: &Path, day: usize) -> Result<(), Box<dyn Error>> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("there is no puzzle for day {}", day).into());
+    }
+
+    let src = root.join("src");
+    let module_path = src.join(format!("day_{}.rs", day));
+    if module_path.exists() {
+        return Err(format!("day {} already exists", day).into());
+    }
+    let fixtures = root.join("fixtures").join(format!("day_{}", day));
+    let example_in = fixtures.join("example.in");
+    let example_out = fixtures.join("example.out");
+    if example_in.exists() || example_out.exists() {
+        return Err(format!("day {} already has an example", day).into());
+    }
+
+    let lib_path = src.join("lib.rs");
+    let registry_path = src.join("registry.rs");
+    let lib = register_module(&fs::read_to_string(&lib_path)?, day)?;
+    let registry = register_solution(&fs::read_to_string(&registry_path)?, day)?;
+
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&module_path)?
+        .write_all(TEMPLATE.as_bytes())?;
+    fs::create_dir_all(&fixtures)?;
+    fs::write(&example_in, "")?;
+    fs::write(&example_out, TEMPLATE_ANSWER)?;
+    fs::write(&lib_path, lib)?;
+    fs::write(&registry_path, registry)?;
+
+    Ok(())
+}
+
+/// Inserts `pub mod day_N;` among the other day modules, keeping them in the order rustfmt sorts
+/// them.
+fn register_module(lib: &str, day: usize) -> Result<String, Box<dyn Error>> {
+    let declaration = format!("pub mod day_{};", day);
+    let mut lines: Vec<&str> = lib.lines().collect();
+
+    if lines.contains(&declaration.as_str()) {
+        return Err(format!("day {} is already declared in lib.rs", day).into());
+    }
+
+    let is_day = |l: &str| l.starts_with("pub mod day_");
+    let first = lines
+        .iter()
+        .position(|l| is_day(l))
+        .ok_or("no day module declared in lib.rs")?;
+    let len = lines[first..].iter().take_while(|l| is_day(l)).count();
+    let name = |l: &str| {
+        l.trim_start_matches("pub mod ")
+            .trim_end_matches(';')
+            .to_owned()
+    };
+    let at = first
+        + lines[first..first + len]
+            .iter()
+            .take_while(|l| name(l) < name(&declaration))
+            .count();
+
+    lines.insert(at, &declaration);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Appends the default solution of the new day to the registry.
+fn register_solution(registry: &str, day: usize) -> Result<String, Box<dyn Error>> {
+    if registry.contains(&format!("Day::new({},", day)) {
+        return Err(format!("day {} is already registered", day).into());
+    }
+
+    let mut lines: Vec<&str> = registry.lines().collect();
+    let days = lines
+        .iter()
+        .position(|l| l.starts_with("pub const DAYS"))
+        .ok_or("no list of days in registry.rs")?;
+    let end = days
+        + lines[days..]
+            .iter()
+            .position(|l| *l == "];")
+            .ok_or("unterminated list of days in registry.rs")?;
+
+    let entry = format!(
+        "    Day::new({}, day_{}::solution, day_{}::validate).with_printer(day_{}::reprint),",
+        day, day, day, day
+    );
+    lines.insert(end, &entry);
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "pub mod day_1;
+pub mod day_10;
+pub mod day_2;
+pub mod day_9;
+pub mod utils;
+
+use std::error::Error;
+";
+
+    const REGISTRY: &str = "pub const DAYS: &[Day] = &[
+    Day::new(1, day_1::solution, day_1::validate)
+        .with_variants(&[(\"sorting\", day_1::solution_sorting)]),
+    Day::new(2, day_2::solution, day_2::validate),
+];
+
+pub fn day(day: usize) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+";
+
+    #[test]
+    fn module_sorted_as_rustfmt() {
+        let lib = register_module(LIB, 3).unwrap();
+        assert!(
+            lib.starts_with("pub mod day_1;\npub mod day_10;\npub mod day_2;\npub mod day_3;\n")
+        );
+        let lib = register_module(LIB, 11).unwrap();
+        assert!(
+            lib.starts_with("pub mod day_1;\npub mod day_10;\npub mod day_11;\npub mod day_2;\n")
+        );
+        assert!(register_module(LIB, 9).is_err());
+    }
+
+    #[test]
+    fn solution_appended() {
+        let registry = register_solution(REGISTRY, 3).unwrap();
+        assert!(registry.contains(
+            "    Day::new(2, day_2::solution, day_2::validate),
+    Day::new(3, day_3::solution, day_3::validate).with_printer(day_3::reprint),
+];"
+        ));
+        assert!(register_solution(REGISTRY, 1).is_err());
+        assert!(register_solution(REGISTRY, 2).is_err());
+    }
+
+    #[test]
+    fn new_day_refuses_to_overwrite() {
+        let root = std::env::temp_dir().join(format!("advent-scaffold-{}", std::process::id()));
+        let src = root.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.rs"), LIB).unwrap();
+        fs::write(src.join("registry.rs"), REGISTRY).unwrap();
+
+        new_day(&root, 3).unwrap();
+        assert_eq!(fs::read_to_string(src.join("day_3.rs")).unwrap(), TEMPLATE);
+        assert!(fs::read_to_string(src.join("lib.rs"))
+            .unwrap()
+            .contains("pub mod day_3;"));
+        assert!(fs::read_to_string(src.join("registry.rs"))
+            .unwrap()
+            .contains(
+                "Day::new(3, day_3::solution, day_3::validate).with_printer(day_3::reprint),"
+            ));
+        let example = root.join("fixtures/day_3");
+        assert_eq!(fs::read_to_string(example.join("example.in")).unwrap(), "");
+        assert_eq!(
+            fs::read_to_string(example.join("example.out")).unwrap(),
+            TEMPLATE_ANSWER
+        );
+
+        fs::write(src.join("day_4.rs"), "// hand written").unwrap();
+        assert!(new_day(&root, 3).is_err());
+        assert!(new_day(&root, 4).is_err());
+        assert_eq!(
+            fs::read_to_string(src.join("day_4.rs")).unwrap(),
+            "// hand written"
+        );
+        assert!(new_day(&root, 26).is_err());
+
+        fs::create_dir_all(root.join("fixtures/day_5")).unwrap();
+        fs::write(root.join("fixtures/day_5/example.in"), "1").unwrap();
+        assert!(new_day(&root, 5).is_err());
+        assert!(!src.join("day_5.rs").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
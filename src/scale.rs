@@ -0,0 +1,240 @@
+use std::{
+    fmt::{self, Display, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    input::ParseError,
+    registry::{Day, SolveError},
+    utils::Rng,
+};
+
+/// Size of the first generated input, each following one is twice as large.
+const FIRST_SIZE: usize = 256;
+const MAX_STEPS: usize = 16;
+/// Runs faster than this are repeated and the fastest one kept, to smooth out the noise.
+const MIN_TIME: Duration = Duration::from_millis(20);
+const MAX_REPEATS: usize = 5;
+/// Shorter runs are dominated by fixed costs and left out of the fit.
+const FIT_THRESHOLD: Duration = Duration::from_micros(200);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub size: usize,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Class {
+    Constant,
+    Logarithmic,
+    Linear,
+    Linearithmic,
+    Quadratic,
+    Cubic,
+}
+
+use Class::*;
+
+impl Class {
+    const ALL: [Class; 6] = [
+        Constant,
+        Logarithmic,
+        Linear,
+        Linearithmic,
+        Quadratic,
+        Cubic,
+    ];
+
+    fn grow(self, n: f64) -> f64 {
+        match self {
+            Constant => 1.0,
+            Logarithmic => n.ln(),
+            Linear => n,
+            Linearithmic => n * n.ln(),
+            Quadratic => n * n,
+            Cubic => n * n * n,
+        }
+    }
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class = match self {
+            Constant => "O(1)",
+            Logarithmic => "O(log n)",
+            Linear => "O(n)",
+            Linearithmic => "O(n log n)",
+            Quadratic => "O(n^2)",
+            Cubic => "O(n^3)",
+        };
+        f.write_str(class)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub class: Class,
+    /// Slope of the times against the sizes on a log-log scale.
+    pub exponent: f64,
+}
+
+impl Fit {
+    pub fn quadratic_or_worse(&self) -> bool {
+        self.class >= Quadratic
+    }
+}
+
+/// Times the day on generated inputs of doubling size until a run takes longer than `budget`.
+pub fn measure(day: &Day, budget: Duration) -> Result<Vec<Sample>, SolveError> {
+    let generate = day
+        .generate
+        .ok_or_else(|| ParseError::new(format!("day {} has no input generator", day.day)))?;
+    let params = day.params();
+    let mut rng = Rng::new(day.day as u64);
+    let mut samples = vec![];
+
+    for step in 0..MAX_STEPS {
+        let size = FIRST_SIZE << step;
+        let input = generate(size, &mut rng);
+
+        let mut fastest = Duration::MAX;
+        let mut spent = Duration::ZERO;
+        for _ in 0..MAX_REPEATS {
+            let start = Instant::now();
+            (day.solution)(&input, &params)?;
+            let time = start.elapsed();
+            fastest = fastest.min(time);
+            spent += time;
+            if spent >= MIN_TIME {
+                break;
+            }
+        }
+
+        samples.push(Sample {
+            size,
+            time: fastest,
+        });
+        if fastest >= budget {
+            break;
+        }
+    }
+
+    Ok(samples)
+}
+
+/// The class fitting the samples best, measured by relative error so that the small sizes count
+/// as much as the large ones. `None` if too few runs were long enough to tell.
+pub fn fit(samples: &[Sample]) -> Option<Fit> {
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .filter(|s| s.time >= FIT_THRESHOLD)
+        .map(|s| (s.size as f64, s.time.as_secs_f64()))
+        .collect();
+    if points.len() < 3 {
+        return None;
+    }
+
+    let error = |class: Class| {
+        // least squares of (c * f(n) - t) / t, minimized by c = sum(f/t) / sum((f/t)^2)
+        let ratios: Vec<f64> = points.iter().map(|&(n, t)| class.grow(n) / t).collect();
+        let c = ratios.iter().sum::<f64>() / ratios.iter().map(|r| r * r).sum::<f64>();
+        ratios.iter().map(|r| (c * r - 1.0).powi(2)).sum::<f64>()
+    };
+    let class = Class::ALL
+        .into_iter()
+        .min_by(|a, b| error(*a).total_cmp(&error(*b)))
+        .expect("not empty");
+
+    let logs: Vec<(f64, f64)> = points.iter().map(|(n, t)| (n.ln(), t.ln())).collect();
+    let len = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / len;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / len;
+    let exponent = logs
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>()
+        / logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+
+    Some(Fit { class, exponent })
+}
+
+pub fn render(day: usize, samples: &[Sample]) -> String {
+    let mut out = String::new();
+    writeln!(out, "Day {}", day).unwrap();
+    writeln!(out, "{:>10}  {:>12}", "size", "time").unwrap();
+    for sample in samples {
+        writeln!(out, "{:>10}  {:>12.3?}", sample.size, sample.time).unwrap();
+    }
+
+    match fit(samples) {
+        Some(fit) => {
+            write!(
+                out,
+                "best fit {} (log-log slope {:.2})",
+                fit.class, fit.exponent
+            )
+            .unwrap();
+            if fit.quadratic_or_worse() {
+                out.push_str(", quadratic or worse");
+            }
+            out.push('\n');
+        }
+        None => out.push_str("too few long enough runs to fit, raise the budget\n"),
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::DAYS;
+
+    fn samples(time: impl Fn(f64) -> f64) -> Vec<Sample> {
+        (0..8)
+            .map(|step| {
+                let size = FIRST_SIZE << step;
+                Sample {
+                    size,
+                    time: Duration::from_secs_f64(time(size as f64)),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classes_fitted() {
+        let linear = fit(&samples(|n| 1e-6 * n)).unwrap();
+        assert_eq!(linear.class, Linear);
+        assert!((linear.exponent - 1.0).abs() < 0.01);
+        assert!(!linear.quadratic_or_worse());
+
+        let fit_of = |time: fn(f64) -> f64| fit(&samples(time)).unwrap().class;
+        assert_eq!(fit_of(|n| 1e-7 * n * n.ln()), Linearithmic);
+        assert_eq!(fit_of(|n| 1e-8 * n * n), Quadratic);
+        assert_eq!(fit_of(|n| 1e-10 * n * n * n), Cubic);
+        // a little noise and a fixed cost don't change the answer
+        assert_eq!(
+            fit_of(|n| 1e-8 * n * n * (1.0 + 0.05 * (n.ln() * 7.0).sin()) + 1e-4),
+            Quadratic
+        );
+
+        assert!(fit(&samples(|_| 1e-6)).is_none());
+    }
+
+    #[test]
+    fn generated_inputs_solved() {
+        for day in DAYS {
+            if let Some(generate) = day.generate {
+                let input = generate(100, &mut Rng::new(0));
+                assert!(
+                    (day.solution)(&input, &day.params()).is_ok(),
+                    "day {}: {}",
+                    day.day,
+                    input
+                );
+            }
+        }
+    }
+}
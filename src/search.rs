@@ -0,0 +1,644 @@
+//! A* searches of the days whose hand-written heuristics decide the answer. Under [`instrumented`]
+//! every search counts its work, and can check its heuristic against the exact remaining costs.
+
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fmt::{self, Debug, Display, Write},
+    hash::Hash,
+};
+
+use crate::{
+    checkpoint::{Checkpoint, Snapshot},
+    utils::FastMap,
+};
+
+/// Nodes expanded between two looks at the clock.
+const CHECK_EVERY: usize = 256;
+
+/// States explored at most to check a heuristic, beyond that the input is too large to check.
+pub const CHECK_LIMIT: usize = 200_000;
+
+struct Open<N> {
+    /// Cost so far plus the heuristic.
+    estimate: i64,
+    cost: i64,
+    node: N,
+}
+
+impl<N> PartialEq for Open<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for Open<N> {}
+
+impl<N> PartialOrd for Open<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Open<N> {
+    // the heap pops the greatest: the lowest estimate, then the deepest node
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then(self.cost.cmp(&other.cost))
+    }
+}
+
+/// Work done by one search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Nodes whose successors were generated.
+    pub expanded: usize,
+    /// Successors generated, including those already reached for less.
+    pub generated: usize,
+    pub peak_open: usize,
+}
+
+/// A state the heuristic estimates above its exact remaining cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overestimate {
+    pub node: String,
+    pub heuristic: i64,
+    pub remaining: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admissibility {
+    /// No state reachable from the start is overestimated, out of that many.
+    Admissible(usize),
+    /// The overestimated states, the worst first.
+    Overestimates(Vec<Overestimate>),
+    /// More than [`CHECK_LIMIT`] states are reachable from the start.
+    TooLarge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub stats: Stats,
+    /// Only checked when asked to.
+    pub heuristic: Option<Admissibility>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expanded {}, generated {}, peak open set {}",
+            self.stats.expanded, self.stats.generated, self.stats.peak_open
+        )?;
+        match &self.heuristic {
+            None => Ok(()),
+            Some(Admissibility::Admissible(states)) => {
+                write!(f, "; heuristic admissible on {} states", states)
+            }
+            Some(Admissibility::Overestimates(over)) => write!(
+                f,
+                "; heuristic overestimates {} states, worst {} estimated {} for {}",
+                over.len(),
+                over[0].node,
+                over[0].heuristic,
+                over[0].remaining
+            ),
+            Some(Admissibility::TooLarge) => write!(
+                f,
+                "; heuristic not checked, more than {} states",
+                CHECK_LIMIT
+            ),
+        }
+    }
+}
+
+struct Instruments {
+    check_heuristic: bool,
+    reports: Vec<Report>,
+}
+
+thread_local! {
+    static INSTRUMENTS: RefCell<Option<Instruments>> = const { RefCell::new(None) };
+}
+
+/// Runs a solution, reporting every search it does on this thread in order. With
+/// `check_heuristic` each search first explores all states reachable from its start to find the
+/// exact remaining costs, only practical on small inputs.
+pub fn instrumented<T>(check_heuristic: bool, f: impl FnOnce() -> T) -> (T, Vec<Report>) {
+    let outer = INSTRUMENTS.with(|i| {
+        i.replace(Some(Instruments {
+            check_heuristic,
+            reports: vec![],
+        }))
+    });
+    let result = f();
+    let instruments = INSTRUMENTS.with(|i| i.replace(outer));
+
+    (result, instruments.map_or(vec![], |i| i.reports))
+}
+
+/// Whether searches are instrumented, and if so whether heuristics are checked.
+fn instruments() -> Option<bool> {
+    INSTRUMENTS.with(|i| i.borrow().as_ref().map(|i| i.check_heuristic))
+}
+
+/// Runs an instrumented search, with the searches it does itself, such as those of day 16's
+/// heuristic, left out of the reports.
+fn reported<T>(f: impl FnOnce() -> (T, Report)) -> T {
+    let outer = INSTRUMENTS.with(|i| i.take());
+    let (result, report) = f();
+    INSTRUMENTS.with(|i| {
+        *i.borrow_mut() = outer;
+        if let Some(i) = i.borrow_mut().as_mut() {
+            i.reports.push(report);
+        }
+    });
+    result
+}
+
+/// The exact cost from every state reachable from `start` to the nearest success, by a Dijkstra
+/// from the successes over the reversed steps. Nodes are reopened when their cost drops, so the
+/// negative steps of days 16 and 19 are fine as long as they form no cycle.
+fn check_heuristic<N, FN, IN, FH, FS>(
+    start: &N,
+    successors: &mut FN,
+    heuristic: &mut FH,
+    success: &mut FS,
+) -> Admissibility
+where
+    N: Eq + Hash + Clone + Debug,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, i64)>,
+    FH: FnMut(&N) -> i64,
+    FS: FnMut(&N) -> bool,
+{
+    let mut ids = FastMap::from_iter([(start.clone(), 0)]);
+    let mut nodes = vec![start.clone()];
+    let mut predecessors: Vec<Vec<(usize, i64)>> = vec![vec![]];
+    let mut goals = vec![];
+
+    let mut next = 0;
+    while next < nodes.len() {
+        if nodes.len() > CHECK_LIMIT {
+            return Admissibility::TooLarge;
+        }
+        let node = nodes[next].clone();
+        if success(&node) {
+            goals.push(next);
+        } else {
+            for (succ, step) in successors(&node) {
+                let id = *ids.entry(succ.clone()).or_insert_with(|| {
+                    nodes.push(succ);
+                    predecessors.push(vec![]);
+                    nodes.len() - 1
+                });
+                predecessors[id].push((next, step));
+            }
+        }
+        next += 1;
+    }
+
+    let mut remaining: Vec<Option<i64>> = vec![None; nodes.len()];
+    let mut open = BinaryHeap::new();
+    for &goal in &goals {
+        remaining[goal] = Some(0);
+        open.push(Open {
+            estimate: 0,
+            cost: 0,
+            node: goal,
+        });
+    }
+    while let Some(Open { estimate, node, .. }) = open.pop() {
+        if remaining[node] != Some(estimate) {
+            continue;
+        }
+        for &(pred, step) in &predecessors[node] {
+            let cost = estimate + step;
+            if remaining[pred].is_none_or(|best| cost < best) {
+                remaining[pred] = Some(cost);
+                open.push(Open {
+                    estimate: cost,
+                    cost: 0,
+                    node: pred,
+                });
+            }
+        }
+    }
+
+    let mut over: Vec<Overestimate> = nodes
+        .iter()
+        .zip(remaining)
+        .filter_map(|(node, remaining)| {
+            let remaining = remaining?;
+            let heuristic = heuristic(node);
+            (heuristic > remaining).then(|| Overestimate {
+                node: format!("{:?}", node),
+                heuristic,
+                remaining,
+            })
+        })
+        .collect();
+    if over.is_empty() {
+        return Admissibility::Admissible(nodes.len());
+    }
+    over.sort_by_key(|o| o.remaining - o.heuristic);
+    Admissibility::Overestimates(over)
+}
+
+struct Search<N> {
+    open: BinaryHeap<Open<N>>,
+    /// Lowest known cost of every node reached.
+    seen: FastMap<N, i64>,
+    stats: Stats,
+}
+
+impl<N: Eq + Hash + Clone> Search<N> {
+    fn new(start: &N, heuristic: i64) -> Self {
+        Self {
+            open: BinaryHeap::from([Open {
+                estimate: heuristic,
+                cost: 0,
+                node: start.clone(),
+            }]),
+            seen: FastMap::from_iter([(start.clone(), 0)]),
+            stats: Stats {
+                peak_open: 1,
+                ..Stats::default()
+            },
+        }
+    }
+
+    /// Runs the search to the end, calling `expanded` after every expansion.
+    fn run<IN>(
+        &mut self,
+        mut successors: impl FnMut(&N) -> IN,
+        mut heuristic: impl FnMut(&N) -> i64,
+        mut success: impl FnMut(&N) -> bool,
+        mut expanded: impl FnMut(&Self),
+    ) -> Option<i64>
+    where
+        IN: IntoIterator<Item = (N, i64)>,
+    {
+        while let Some(Open { cost, node, .. }) = self.open.pop() {
+            if self.seen.get(&node).is_some_and(|&best| cost > best) {
+                continue;
+            }
+            if success(&node) {
+                return Some(cost);
+            }
+
+            for (next, step) in successors(&node) {
+                self.stats.generated += 1;
+                let next_cost = cost + step;
+                if self.seen.get(&next).is_some_and(|&best| best <= next_cost) {
+                    continue;
+                }
+                self.open.push(Open {
+                    estimate: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next.clone(),
+                });
+                self.seen.insert(next, next_cost);
+            }
+
+            self.stats.expanded += 1;
+            self.stats.peak_open = self.stats.peak_open.max(self.open.len());
+            expanded(self);
+        }
+
+        None
+    }
+}
+
+impl<N: Eq + Hash + Clone + Snapshot> Search<N> {
+    fn save(&self) -> String {
+        let mut body = String::new();
+        let mut fields = vec![];
+        let mut line = |tag: &str, numbers: &[i64], node: &N| {
+            fields.clear();
+            node.save(&mut fields);
+            write!(body, "{}", tag).unwrap();
+            for n in numbers.iter().chain(&fields) {
+                write!(body, " {}", n).unwrap();
+            }
+            body.push('\n');
+        };
+
+        for open in &self.open {
+            line("open", &[open.estimate, open.cost], &open.node);
+        }
+        for (node, cost) in &self.seen {
+            line("seen", &[*cost], node);
+        }
+        body
+    }
+
+    fn restore(body: &str) -> Option<Self> {
+        let mut search = Self {
+            open: BinaryHeap::new(),
+            seen: FastMap::default(),
+            stats: Stats::default(),
+        };
+        for line in body.lines() {
+            let (tag, rest) = line.split_once(' ')?;
+            let mut fields = rest.split(' ').map_while(|n| n.parse::<i64>().ok());
+            match tag {
+                "open" => {
+                    let (estimate, cost) = (fields.next()?, fields.next()?);
+                    let node = N::restore(&mut fields)?;
+                    search.open.push(Open {
+                        estimate,
+                        cost,
+                        node,
+                    });
+                }
+                "seen" => {
+                    let cost = fields.next()?;
+                    search.seen.insert(N::restore(&mut fields)?, cost);
+                }
+                _ => return None,
+            }
+        }
+        Some(search)
+    }
+}
+
+/// The cost of the cheapest path from `start` to a node satisfying `success`, for searches that
+/// are never checkpointed. This is `pathfinding::prelude::astar` unless instrumented.
+pub fn astar_plain<N, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+) -> Option<i64>
+where
+    N: Eq + Hash + Clone + Debug,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, i64)>,
+    FH: FnMut(&N) -> i64,
+    FS: FnMut(&N) -> bool,
+{
+    let Some(check) = instruments() else {
+        return pathfinding::prelude::astar(start, successors, heuristic, success)
+            .map(|(_, cost)| cost);
+    };
+
+    reported(|| {
+        let admissibility =
+            check.then(|| check_heuristic(start, &mut successors, &mut heuristic, &mut success));
+        let mut search = Search::new(start, heuristic(start));
+        let cost = search.run(successors, heuristic, success, |_| ());
+        let report = Report {
+            stats: search.stats,
+            heuristic: admissibility,
+        };
+        (cost, report)
+    })
+}
+
+/// The cost of the cheapest path from `start` to a node satisfying `success`. Without a checkpoint
+/// this is [`astar_plain`]. With one the open set is saved every so often and a search interrupted
+/// on the same input resumes from there, the checkpoint then holds the final cost until cleared.
+pub fn astar<N, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+    checkpoint: Option<&mut Checkpoint>,
+) -> Option<i64>
+where
+    N: Eq + Hash + Clone + Debug + Snapshot,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, i64)>,
+    FH: FnMut(&N) -> i64,
+    FS: FnMut(&N) -> bool,
+{
+    let checkpoint = match checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => return astar_plain(start, successors, heuristic, success),
+    };
+
+    let resumed = checkpoint.load();
+    if let Some(cost) = resumed.as_deref().and_then(|r| r.strip_prefix("done ")) {
+        return cost.trim().parse().ok();
+    }
+    let check = instruments();
+    let run = || {
+        let admissibility = check
+            .filter(|check| *check)
+            .map(|_| check_heuristic(start, &mut successors, &mut heuristic, &mut success));
+        let mut search = resumed
+            .as_deref()
+            .and_then(Search::restore)
+            .unwrap_or_else(|| Search::new(start, heuristic(start)));
+
+        let cost = search.run(successors, heuristic, success, |search| {
+            if search.stats.expanded % CHECK_EVERY == 0 && checkpoint.due() {
+                if let Err(e) = checkpoint.save(&search.save()) {
+                    eprintln!("checkpoint not saved: {}", e);
+                }
+            }
+        });
+        if let Some(cost) = cost {
+            let _ = checkpoint.save(&format!("done {}\n", cost));
+        }
+        let report = Report {
+            stats: search.stats,
+            heuristic: admissibility,
+        };
+        (cost, report)
+    };
+
+    match check {
+        Some(_) => reported(run),
+        None => run().0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, panic, time::Duration};
+
+    use super::*;
+    use crate::checkpoint::Checkpoints;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Cell2(i64, i64);
+
+    impl Snapshot for Cell2 {
+        fn save(&self, fields: &mut Vec<i64>) {
+            fields.extend([self.0, self.1]);
+        }
+
+        fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self> {
+            Some(Self(fields.next()?, fields.next()?))
+        }
+    }
+
+    const SIDE: i64 = 200;
+
+    /// Stepping right is cheap in odd rows, down is cheap in odd columns.
+    fn grid(Cell2(x, y): &Cell2) -> Vec<(Cell2, i64)> {
+        let mut nexts = vec![];
+        if *x < SIDE {
+            nexts.push((Cell2(x + 1, *y), 1 + y % 2));
+        }
+        if *y < SIDE {
+            nexts.push((Cell2(*x, y + 1), 1 + x % 2));
+        }
+        nexts
+    }
+
+    /// No help from the heuristic, so the search has to go through most of the grid.
+    fn heuristic(_: &Cell2) -> i64 {
+        0
+    }
+
+    fn goal(node: &Cell2) -> bool {
+        *node == Cell2(SIDE, SIDE)
+    }
+
+    #[test]
+    fn interrupted_search_resumed() {
+        let fresh = astar(&Cell2(0, 0), grid, heuristic, goal, None).unwrap();
+        assert_eq!(fresh, 2 * SIDE);
+
+        let dir = std::env::temp_dir().join(format!("advent-search-{}", std::process::id()));
+        let checkpoints = Checkpoints::new(&dir, "grid", Duration::ZERO);
+
+        let expanded = Cell::new(0);
+        let interrupted = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let mut checkpoint = checkpoints.get("grid");
+            let successors = |node: &Cell2| {
+                expanded.set(expanded.get() + 1);
+                assert!(expanded.get() < 5000, "interrupted");
+                grid(node)
+            };
+            astar(
+                &Cell2(0, 0),
+                successors,
+                heuristic,
+                goal,
+                Some(&mut checkpoint),
+            )
+        }));
+        assert!(interrupted.is_err());
+
+        let resumed_expanded = Cell::new(0);
+        let mut checkpoint = checkpoints.get("grid");
+        let successors = |node: &Cell2| {
+            resumed_expanded.set(resumed_expanded.get() + 1);
+            grid(node)
+        };
+        let resumed = astar(
+            &Cell2(0, 0),
+            successors,
+            heuristic,
+            goal,
+            Some(&mut checkpoint),
+        );
+        assert_eq!(resumed, Some(fresh));
+        assert!(resumed_expanded.get() > 0);
+
+        let fresh_expanded = Cell::new(0);
+        astar(
+            &Cell2(0, 0),
+            |node: &Cell2| {
+                fresh_expanded.set(fresh_expanded.get() + 1);
+                grid(node)
+            },
+            heuristic,
+            goal,
+            None,
+        );
+        assert!(resumed_expanded.get() + 4000 < fresh_expanded.get());
+
+        // a finished search is answered straight from its checkpoint
+        let done = astar(
+            &Cell2(0, 0),
+            |_| vec![],
+            heuristic,
+            goal,
+            Some(&mut checkpoint),
+        );
+        assert_eq!(done, Some(fresh));
+
+        checkpoint.clear();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn searches_instrumented() {
+        let (cost, reports) = instrumented(false, || {
+            astar(&Cell2(0, 0), grid, heuristic, goal, None).unwrap()
+                + astar_plain(&0, |n| [(n + 1, 1)], |_| 0, |n| *n == 10).unwrap()
+        });
+        assert_eq!(cost, 2 * SIDE + 10);
+        assert_eq!(reports.len(), 2);
+
+        let Stats {
+            expanded,
+            generated,
+            peak_open,
+        } = reports[0].stats;
+        assert!(expanded > SIDE as usize && generated > expanded);
+        assert!(peak_open > 1 && peak_open < generated);
+        assert_eq!(reports[0].heuristic, None);
+        assert_eq!(
+            reports[1].stats,
+            Stats {
+                expanded: 10,
+                generated: 10,
+                peak_open: 1
+            }
+        );
+
+        // nothing is recorded outside
+        astar(&Cell2(0, 0), grid, heuristic, goal, None);
+        assert_eq!(instrumented(false, || ()).1, []);
+    }
+
+    #[test]
+    fn overestimates_reported() {
+        let states = ((SIDE + 1) * (SIDE + 1)) as usize;
+        let (_, reports) = instrumented(true, || {
+            astar(&Cell2(0, 0), grid, heuristic, goal, None);
+            // every step costs at least 1
+            let manhattan = |Cell2(x, y): &Cell2| (SIDE - x) + (SIDE - y);
+            astar(&Cell2(0, 0), grid, manhattan, goal, None);
+            let twice = |Cell2(x, y): &Cell2| 2 * ((SIDE - x) + (SIDE - y));
+            astar(&Cell2(0, 0), grid, twice, goal, None);
+        });
+
+        assert_eq!(
+            reports[0].heuristic,
+            Some(Admissibility::Admissible(states))
+        );
+        assert_eq!(
+            reports[1].heuristic,
+            Some(Admissibility::Admissible(states))
+        );
+        let Some(Admissibility::Overestimates(over)) = &reports[2].heuristic else {
+            panic!("{}", reports[2]);
+        };
+        assert!(over.iter().all(|o| o.heuristic > o.remaining));
+        assert_eq!(over[0].node, "Cell2(0, 0)");
+        assert_eq!((over[0].heuristic, over[0].remaining), (4 * SIDE, 2 * SIDE));
+        assert!(reports[2]
+            .to_string()
+            .contains("worst Cell2(0, 0) estimated"));
+    }
+
+    #[test]
+    fn large_graphs_not_checked() {
+        let end = CHECK_LIMIT as i64 + 10;
+        let (cost, reports) = instrumented(true, || {
+            astar_plain(&0, |n| [(n + 1, 1)], |_| 0, |n| *n == end)
+        });
+        assert_eq!(cost, Some(end));
+        assert_eq!(reports[0].heuristic, Some(Admissibility::TooLarge));
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    input::normalize,
+    json::Value,
+    registry::{self, SolveError, DAYS},
+    timing::timed,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Longest request line plus headers accepted.
+    pub max_head: usize,
+    pub max_body: usize,
+    /// How long a client may take to send its request.
+    pub read_timeout: Duration,
+    /// How long a solution may take before the request is answered with a timeout. The solution
+    /// itself can't be interrupted and runs to completion in the background.
+    pub solve_timeout: Duration,
+    /// Connections served at once, counting those whose solution still runs after a timeout.
+    pub max_connections: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_head: 8 * 1024,
+            max_body: 1024 * 1024,
+            read_timeout: Duration::from_secs(10),
+            solve_timeout: Duration::from_secs(60),
+            max_connections: 16,
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: Value::Object(vec![("error".to_owned(), Value::String(message.into()))]),
+        }
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            500 => "Internal Server Error",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            _ => "",
+        };
+        let body = self.body.to_string();
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: String,
+}
+
+/// Serves the solutions over HTTP until the listener fails:
+///
+/// - `GET /days` lists the days with their variants and parameters.
+/// - `POST /solve/{day}` solves the input in the body. The query may select a `variant` and
+///   override parameters, as in `/solve/15?row=10&bound=20`.
+pub fn serve(listener: TcpListener, limits: Limits) -> io::Result<()> {
+    let active = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        if active.fetch_add(1, Ordering::SeqCst) >= limits.max_connections {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let _ = Response::error(503, "too many requests").write_to(&mut stream);
+            linger(&mut stream);
+            continue;
+        }
+
+        let slot = Arc::new(Slot(Arc::clone(&active)));
+        thread::spawn(move || {
+            let response = match read_request(&mut stream, &limits) {
+                Ok(request) => route(request, &limits, &slot),
+                Err(response) => response,
+            };
+            let _ = response.write_to(&mut stream);
+            linger(&mut stream);
+        });
+    }
+
+    Ok(())
+}
+
+/// A place among the connections served at once, given back when the last of the connection and
+/// its solution is done with it.
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Closing with unread data resets the connection, which may destroy the response before the
+/// client reads it. Rejected requests are drained a little first so the client sees why.
+fn linger(stream: &mut TcpStream) {
+    let _ = stream.shutdown(Shutdown::Write);
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
+    let _ = io::copy(&mut Read::take(&mut *stream, 1024 * 1024), &mut io::sink());
+}
+
+fn read_request(stream: &mut TcpStream, limits: &Limits) -> Result<Request, Response> {
+    let timed_out = |e: io::Error| match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            Response::error(408, "request not received in time")
+        }
+        _ => Response::error(400, e.to_string()),
+    };
+
+    stream
+        .set_read_timeout(Some(limits.read_timeout))
+        .map_err(|e| Response::error(500, e.to_string()))?;
+    let deadline = Instant::now() + limits.read_timeout;
+    let mut reader = BufReader::new(Read::take(&mut *stream, limits.max_head as u64));
+
+    let mut head = vec![];
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(timed_out)? == 0 {
+            return Err(if reader.get_ref().limit() == 0 {
+                Response::error(431, "request head too large")
+            } else {
+                Response::error(400, "incomplete request")
+            });
+        }
+        if Instant::now() > deadline {
+            return Err(Response::error(408, "request not received in time"));
+        }
+        let line = line.trim_end().to_owned();
+        if line.is_empty() {
+            break;
+        }
+        head.push(line);
+    }
+
+    let mut request_line = head.first().map(|l| l.split(' ')).into_iter().flatten();
+    let (method, target) = match (request_line.next(), request_line.next()) {
+        (Some(method), Some(target)) => (method.to_owned(), target.to_owned()),
+        _ => return Err(Response::error(400, "malformed request line")),
+    };
+
+    let header = |name: &str| {
+        head[1..].iter().find_map(|l| {
+            let (n, v) = l.split_once(':')?;
+            n.trim().eq_ignore_ascii_case(name).then(|| v.trim())
+        })
+    };
+    if header("Transfer-Encoding").is_some() {
+        return Err(Response::error(
+            411,
+            "only Content-Length bodies are accepted",
+        ));
+    }
+    let length = match header("Content-Length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| Response::error(400, "invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > limits.max_body {
+        return Err(Response::error(
+            413,
+            format!("body larger than {} bytes", limits.max_body),
+        ));
+    }
+
+    // whatever was buffered past the head belongs to the body
+    let mut body = reader.buffer().to_vec();
+    body.truncate(length);
+    let stream = reader.into_inner().into_inner();
+    while body.len() < length {
+        let mut chunk = vec![0; (length - body.len()).min(64 * 1024)];
+        let n = stream.read(&mut chunk).map_err(timed_out)?;
+        if n == 0 {
+            return Err(Response::error(400, "incomplete body"));
+        }
+        if Instant::now() > deadline {
+            return Err(Response::error(408, "request not received in time"));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8(body).map_err(|_| Response::error(400, "body is not UTF-8"))?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            Ok((percent_decode(k)?, percent_decode(v)?))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Request {
+        method,
+        path: path.to_owned(),
+        query,
+        body,
+    })
+}
+
+/// Decodes a component of a query, where `+` stands for a space and `%XX` for any byte.
+fn percent_decode(component: &str) -> Result<String, Response> {
+    let invalid = || Response::error(400, format!("invalid query component {:?}", component));
+    let mut bytes = component.bytes();
+    let mut decoded = vec![];
+
+    while let Some(b) = bytes.next() {
+        match b {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = [bytes.next(), bytes.next()];
+                let hex = match hex {
+                    [Some(hi), Some(lo)] => [hi, lo],
+                    _ => return Err(invalid()),
+                };
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            _ => decoded.push(b),
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+fn route(request: Request, limits: &Limits, slot: &Arc<Slot>) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => days(),
+        ("POST", ["solve", day]) => match day.parse::<usize>() {
+            Ok(day) => solve(day, &request, limits, slot),
+            Err(_) => Response::error(404, format!("no day {}", day)),
+        },
+        (_, ["days"]) | (_, ["solve", _]) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn days() -> Response {
+    let days = DAYS
+        .iter()
+        .map(|day| {
+            let variants = day
+                .all_variants()
+                .map(|(name, _)| Value::from(name))
+                .collect();
+            let params = day
+                .params
+                .iter()
+                .map(|p| {
+                    Value::Object(vec![
+                        ("name".to_owned(), Value::from(p.name)),
+                        ("default".to_owned(), Value::from(p.default as f64)),
+                    ])
+                })
+                .collect();
+
+            Value::Object(vec![
+                ("day".to_owned(), Value::from(day.day as f64)),
+                ("variants".to_owned(), Value::Array(variants)),
+                ("params".to_owned(), Value::Array(params)),
+            ])
+        })
+        .collect();
+
+    Response::ok(Value::Array(days))
+}
+
+fn solve(day: usize, request: &Request, limits: &Limits, slot: &Arc<Slot>) -> Response {
+    let registered = match registry::day(day) {
+        Some(registered) => registered,
+        None => return Response::error(404, format!("no solution for day {}", day)),
+    };
+
+    let mut variant = registry::DEFAULT;
+    let mut params = registered.params();
+    for (key, value) in &request.query {
+        if key == "variant" {
+            variant = value;
+        } else if let Err(e) = params.set(&format!("{}={}", key, value)) {
+            return Response::error(400, e);
+        }
+    }
+    let solution = match registered.variant(variant) {
+        Some(solution) => solution,
+        None => return Response::error(404, format!("no variant named {}", variant)),
+    };
+
+    let input = normalize(&request.body);
+    let validate = registered.validate;
+    let (sender, receiver) = mpsc::channel();
+    // a solution outliving its request keeps the connection's slot until it is done
+    let slot = Arc::clone(slot);
+    thread::spawn(move || {
+        let outcome = panic::catch_unwind(|| -> Result<_, SolveError> {
+            let (parsed, parse) = timed(|| validate(&input));
+            parsed?;
+            let (answer, solve) = timed(|| solution(&input, &params));
+            Ok((answer?, parse, solve))
+        });
+        let _ = sender.send(outcome);
+        drop(slot);
+    });
+
+    match receiver.recv_timeout(limits.solve_timeout) {
+        Ok(Ok(Ok((answer, parse, solve)))) => {
+            let parts = registry::parts(&answer)
+                .into_iter()
+                .map(Value::from)
+                .collect();
+            let millis = |d: Duration| Value::from(d.as_secs_f64() * 1000.0);
+            let mut timings = vec![
+                ("parse_ms".to_owned(), millis(parse.total)),
+                ("solve_ms".to_owned(), millis(solve.total)),
+            ];
+            timings.extend(
+                solve
+                    .phases
+                    .iter()
+                    .map(|(name, time)| (format!("{}_ms", name.replace(' ', "_")), millis(*time))),
+            );
+
+            Response::ok(Value::Object(vec![
+                ("day".to_owned(), Value::from(day as f64)),
+                ("variant".to_owned(), Value::from(variant)),
+                ("answer".to_owned(), Value::from(answer.as_str())),
+                ("parts".to_owned(), Value::Array(parts)),
+                ("timings".to_owned(), Value::Object(timings)),
+            ]))
+        }
+        Ok(Ok(Err(SolveError::Parse(e)))) => Response::error(422, e.to_string()),
+        Ok(Ok(Err(SolveError::Overflow(e)))) => Response::error(500, e.to_string()),
+        Ok(Err(_)) => Response::error(500, "solution panicked"),
+        Err(_) => Response::error(504, format!("no answer within {:?}", limits.solve_timeout)),
+    }
+}
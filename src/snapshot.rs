@@ -0,0 +1,109 @@
+//! Expected renderings of the fixtures, stored next to them under `fixtures/day_N/` as
+//! `NAME.RENDERING.snap`. A rendering changed on purpose is accepted by running the tests with
+//! [`UPDATE_VAR`] set, which writes the new renderings over their snapshots.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use itertools::{EitherOrBoth, Itertools};
+
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Whether snapshots are being rewritten rather than checked.
+pub fn updating() -> bool {
+    env::var_os(UPDATE_VAR).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Where the snapshot of a rendering of a fixture is stored under `root`.
+pub fn path(root: &Path, day: usize, fixture: &str, rendering: &str) -> PathBuf {
+    root.join(format!("day_{}", day))
+        .join(format!("{}.{}.snap", fixture, rendering))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Matched,
+    /// Written in update mode, the snapshot was missing or different.
+    Written,
+    Missing,
+    /// The differing lines, see [`diff`].
+    Differs(String),
+}
+
+/// Compares a rendering with its snapshot at `path`, or with `update` writes it there unless they
+/// already match.
+pub fn check(path: &Path, rendered: &str, update: bool) -> io::Result<Outcome> {
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    match expected {
+        Some(expected) if expected == rendered => Ok(Outcome::Matched),
+        _ if update => fs::write(path, rendered).map(|_| Outcome::Written),
+        Some(expected) => Ok(Outcome::Differs(diff(&expected, rendered))),
+        None => Ok(Outcome::Missing),
+    }
+}
+
+/// The lines that differ by line number, the expected one marked `-` and the rendered one `+`.
+pub fn diff(expected: &str, rendered: &str) -> String {
+    let mut out = String::new();
+    for (i, lines) in expected
+        .split('\n')
+        .zip_longest(rendered.split('\n'))
+        .enumerate()
+    {
+        let (expected, rendered) = match lines {
+            EitherOrBoth::Both(e, r) if e == r => continue,
+            EitherOrBoth::Both(e, r) => (Some(e), Some(r)),
+            EitherOrBoth::Left(e) => (Some(e), None),
+            EitherOrBoth::Right(r) => (None, Some(r)),
+        };
+        if let Some(line) = expected {
+            out.push_str(&format!("{:>4} - {}\n", i + 1, line));
+        }
+        if let Some(line) = rendered {
+            out.push_str(&format!("{:>4} + {}\n", i + 1, line));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_and_updated() {
+        let dir = env::temp_dir().join(format!("advent-snapshot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("example.crt.snap");
+
+        assert_eq!(check(&path, "#.\n.#\n", false).unwrap(), Outcome::Missing);
+        assert!(!path.exists());
+        assert_eq!(check(&path, "#.\n.#\n", true).unwrap(), Outcome::Written);
+        assert_eq!(check(&path, "#.\n.#\n", false).unwrap(), Outcome::Matched);
+        assert_eq!(check(&path, "#.\n.#\n", true).unwrap(), Outcome::Matched);
+
+        assert_eq!(
+            check(&path, "#.\n##\n", false).unwrap(),
+            Outcome::Differs("   2 - .#\n   2 + ##\n".to_owned())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "#.\n.#\n");
+        assert_eq!(check(&path, "#.\n##\n", true).unwrap(), Outcome::Written);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "#.\n##\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn extra_lines_diffed() {
+        assert_eq!(diff("a\nb", "a\nb"), "");
+        assert_eq!(diff("a", "a\nb"), "   2 + b\n");
+        assert_eq!(diff("a\nb\n", "a"), "   2 - b\n   3 - \n");
+    }
+}
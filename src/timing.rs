@@ -0,0 +1,107 @@
+//! Time spent in the phases of a solution. Solutions mark the work shared by both parts and each
+//! part with [`phase`], runners collect them with [`timed`]. Outside of [`timed`] phases cost
+//! nothing more than two clock reads.
+
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+/// Work shared by both parts, done once before either of them.
+pub const PREPROCESS: &str = "preprocess";
+pub const PART_ONE: &str = "part one";
+pub const PART_TWO: &str = "part two";
+
+thread_local! {
+    static PHASES: RefCell<Option<Vec<(&'static str, Duration)>>> = const { RefCell::new(None) };
+}
+
+/// Runs one phase of a solution, recorded if the solution runs under [`timed`] on this thread.
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    PHASES.with(|phases| {
+        if let Some(phases) = phases.borrow_mut().as_mut() {
+            phases.push((name, elapsed));
+        }
+    });
+    result
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub total: Duration,
+    /// The phases marked by the solution in the order they ran, none for most days.
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    pub fn phase(&self, name: &str) -> Option<Duration> {
+        self.phases
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, time)| *time)
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3?}", self.total)?;
+        for (i, (name, time)) in self.phases.iter().enumerate() {
+            let sep = if i == 0 { " (" } else { ", " };
+            write!(f, "{}{} {:.3?}", sep, name, time)?;
+        }
+        if !self.phases.is_empty() {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a solution, timing it as a whole and each of the phases it marks.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Timings) {
+    let outer = PHASES.with(|phases| phases.replace(Some(vec![])));
+    let start = Instant::now();
+    let result = f();
+    let total = start.elapsed();
+    let phases = PHASES.with(|phases| phases.replace(outer));
+
+    (
+        result,
+        Timings {
+            total,
+            phases: phases.unwrap_or_default(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_recorded() {
+        assert_eq!(phase(PREPROCESS, || 1), 1);
+
+        let (answer, timings) = timed(|| {
+            let shared = phase(PREPROCESS, || 20);
+            let one = phase(PART_ONE, || shared + 1);
+            let (two, inner) = timed(|| phase(PART_TWO, || shared + 2));
+            assert_eq!(inner.phases.len(), 1);
+            format!("{}, {}", one, two)
+        });
+        assert_eq!(answer, "21, 22");
+
+        let names: Vec<_> = timings.phases.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, [PREPROCESS, PART_ONE]);
+        assert!(timings.phase(PREPROCESS).unwrap() <= timings.total);
+        assert_eq!(timings.phase(PART_TWO), None);
+        assert!(timings.to_string().contains(" (preprocess "));
+
+        let (_, untimed) = timed(|| ());
+        assert_eq!(untimed.phases, []);
+        assert!(!untimed.to_string().contains('('));
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    fmt::Display,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// 2022-12-01T05:00:00Z, midnight of the first day in UTC-5 where the puzzles unlock.
+const FIRST_UNLOCK: u64 = 1_669_870_800;
+const DAY: u64 = 24 * 60 * 60;
+
+/// The site may take a moment to serve a freshly unlocked puzzle.
+const MARGIN: Duration = Duration::from_secs(3);
+
+pub fn unlock_time(day: usize) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + (day as u64 - 1) * DAY)
+}
+
+/// Time left until the puzzle of `day` unlocks, `None` if it already has.
+pub fn until_unlock(day: usize, now: SystemTime) -> Option<Duration> {
+    unlock_time(day)
+        .duration_since(now)
+        .ok()
+        .filter(|d| !d.is_zero())
+}
+
+/// Sleeps until the puzzle of `day` unlocks, reporting the countdown every minute.
+pub fn wait_for_unlock(day: usize) {
+    if until_unlock(day, SystemTime::now()).is_none() {
+        return;
+    }
+
+    while let Some(left) = until_unlock(day, SystemTime::now()) {
+        println!("Day {} unlocks in {}", day, Countdown(left));
+        thread::sleep(left.min(Duration::from_secs(60)));
+    }
+    thread::sleep(MARGIN);
+}
+
+/// A duration in days, hours, minutes and seconds, as in `1d 02h 03m 04s`.
+pub struct Countdown(pub Duration);
+
+impl Display for Countdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // round up, so a countdown never shows 0s before the unlock
+        let secs = self.0.as_secs() + u64::from(self.0.subsec_nanos() > 0);
+        let (days, secs) = (secs / DAY, secs % DAY);
+        let (hours, secs) = (secs / 3600, secs % 3600);
+        let (minutes, secs) = (secs / 60, secs % 60);
+
+        if days > 0 {
+            write!(f, "{}d ", days)?;
+        }
+        write!(f, "{:02}h {:02}m {:02}s", hours, minutes, secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlocks_at_midnight_est() {
+        let first = unlock_time(1);
+        let just_before = first - Duration::from_secs(1);
+        assert_eq!(until_unlock(1, just_before), Some(Duration::from_secs(1)));
+        assert_eq!(until_unlock(1, first), None);
+        assert_eq!(until_unlock(25, first), Some(Duration::from_secs(24 * DAY)));
+        assert_eq!(until_unlock(25, SystemTime::now()), None);
+    }
+
+    #[test]
+    fn countdown() {
+        let secs = |s| Countdown(Duration::from_secs(s)).to_string();
+        assert_eq!(secs(59), "00h 00m 59s");
+        assert_eq!(secs(3 * 3600 + 2 * 60 + 1), "03h 02m 01s");
+        assert_eq!(secs(DAY + 1), "1d 00h 00m 01s");
+        assert_eq!(
+            Countdown(Duration::from_millis(500)).to_string(),
+            "00h 00m 01s"
+        );
+    }
+}
@@ -1,45 +1,219 @@
-use std::ops::{Add, RangeInclusive, Sub};
+use std::{
+    array,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    hash::BuildHasherDefault,
+    iter::successors,
+    ops::{Add, Index, IndexMut, Mul, Neg, RangeInclusive, Sub},
+    slice,
+    str::FromStr,
+};
 
-pub const UP: Coord = Coord { x: 0, y: -1 };
-pub const DOWN: Coord = Coord { x: 0, y: 1 };
-pub const LEFT: Coord = Coord { x: -1, y: 0 };
-pub const RIGHT: Coord = Coord { x: 1, y: 0 };
-pub const STAY: Coord = Coord { x: 0, y: 0 };
+use nom::{
+    character::complete::{self, char},
+    combinator::all_consuming,
+    IResult,
+};
+use rustc_hash::FxHasher;
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Coord {
-    pub x: i64,
-    pub y: i64,
+use crate::{checkpoint::Snapshot, input::ParseError};
+
+/// Hashes the small keys of the solvers (coordinates, bit sets, search states) several times
+/// faster than the standard SipHash, giving up its resistance to crafted collisions that no
+/// puzzle input attempts.
+pub type FastState = BuildHasherDefault<FxHasher>;
+pub type FastMap<K, V> = HashMap<K, V, FastState>;
+pub type FastSet<T> = HashSet<T, FastState>;
+
+pub const UP: Coord = Coord::new(0, -1);
+pub const DOWN: Coord = Coord::new(0, 1);
+pub const LEFT: Coord = Coord::new(-1, 0);
+pub const RIGHT: Coord = Coord::new(1, 0);
+pub const STAY: Coord = Coord::new(0, 0);
+
+/// A point on an integer lattice of `N` dimensions. The coordinates of 2D and 3D points are also
+/// read by name, with `x()`, `y()` and `z()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point<const N: usize>(pub [i64; N]);
+
+pub type Coord = Point<2>;
+
+impl<const N: usize> Default for Point<N> {
+    fn default() -> Self {
+        Self::ORIGIN
+    }
 }
 
-impl Add for Coord {
+impl<const N: usize> Add for Point<N> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self::new(self.x + rhs.x, self.y + rhs.y)
+        Self(array::from_fn(|i| self.0[i] + rhs.0[i]))
     }
 }
 
-impl Sub for Coord {
+impl<const N: usize> Sub for Point<N> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self::new(self.x - rhs.x, self.y - rhs.y)
+        Self(array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.map(|c| -c))
     }
 }
 
-impl Coord {
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<const N: usize> Point<N> {
+    pub const ORIGIN: Self = Self([0; N]);
+
+    pub fn manhattan_distance(self, other: Self) -> i64 {
+        (self - other).0.iter().map(|d| d.abs()).sum()
+    }
+
+    pub fn chebyshev_distance(self, other: Self) -> i64 {
+        (self - other).0.iter().map(|d| d.abs()).max().unwrap_or(0)
+    }
+
+    /// The `2 * N` unit steps along a single axis, axis by axis, the negative one first.
+    pub fn axis_steps() -> impl Iterator<Item = Self> {
+        (0..2 * N).map(|i| {
+            let mut step = Self::ORIGIN;
+            step.0[i / 2] = if i % 2 == 0 { -1 } else { 1 };
+            step
+        })
+    }
+
+    /// The `3^N - 1` steps to every point touching this one, even by a corner, with the first
+    /// axis changing fastest.
+    pub fn all_steps() -> impl Iterator<Item = Self> {
+        (0..3usize.pow(N as u32))
+            .map(|mut i| {
+                Self(array::from_fn(|_| {
+                    let d = (i % 3) as i64 - 1;
+                    i /= 3;
+                    d
+                }))
+            })
+            .filter(|step| *step != Self::ORIGIN)
+    }
+
+    pub fn axis_neighbors(self) -> impl Iterator<Item = Self> {
+        Self::axis_steps().map(move |d| self + d)
+    }
+
+    pub fn all_neighbors(self) -> impl Iterator<Item = Self> {
+        Self::all_steps().map(move |d| self + d)
+    }
+}
+
+impl Point<2> {
     pub const fn new(x: i64, y: i64) -> Self {
-        Self { x, y }
+        Self([x, y])
+    }
+
+    pub const fn x(self) -> i64 {
+        self.0[0]
+    }
+
+    pub const fn y(self) -> i64 {
+        self.0[1]
     }
 
     pub fn to_tuple(self) -> (i64, i64) {
-        (self.x, self.y)
+        (self.x(), self.y())
     }
+}
 
-    pub fn manhattan_distance(self, other: Self) -> i64 {
-        let (dx, dy) = (self - other).to_tuple();
-        dx.abs() + dy.abs()
+impl Point<3> {
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self([x, y, z])
+    }
+
+    pub const fn x(self) -> i64 {
+        self.0[0]
+    }
+
+    pub const fn y(self) -> i64 {
+        self.0[1]
+    }
+
+    pub const fn z(self) -> i64 {
+        self.0[2]
+    }
+}
+
+/// Comma separated coordinates such as `2,-3,5`, exactly `N` of them.
+pub fn p_point<const N: usize>(mut input: &str) -> IResult<&str, Point<N>> {
+    let mut point = Point::ORIGIN;
+    for (i, c) in point.0.iter_mut().enumerate() {
+        if i > 0 {
+            (input, _) = char(',')(input)?;
+        }
+        (input, *c) = complete::i64(input)?;
+    }
+    Ok((input, point))
+}
+
+impl<const N: usize> FromStr for Point<N> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (_, point) = all_consuming(p_point)(s)?;
+        Ok(point)
+    }
+}
+
+/// The smallest box holding some points, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds<const N: usize> {
+    pub min: Point<N>,
+    pub max: Point<N>,
+}
+
+impl<const N: usize> Bounds<N> {
+    /// The box around `points`, `None` if there are none.
+    pub fn of(points: impl IntoIterator<Item = Point<N>>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, p| {
+            Some(match bounds {
+                None => Self { min: p, max: p },
+                Some(Self { min, max }) => Self {
+                    min: Point(array::from_fn(|i| min.0[i].min(p.0[i]))),
+                    max: Point(array::from_fn(|i| max.0[i].max(p.0[i]))),
+                },
+            })
+        })
+    }
+
+    /// The box with `margin` more on every side.
+    pub fn grown(self, margin: i64) -> Self {
+        let margin = Point([margin; N]);
+        Self {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    pub fn contains(&self, p: Point<N>) -> bool {
+        (0..N).all(|i| self.min.0[i] <= p.0[i] && p.0[i] <= self.max.0[i])
+    }
+
+    /// The number of points along every axis.
+    pub fn size(&self) -> Point<N> {
+        self.max - self.min + Point([1; N])
     }
 }
 
@@ -99,45 +273,143 @@ impl IntoIterator for Closed {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct BitSet(u64);
+const WORD: usize = u64::BITS as usize;
+
+/// A set of small integers, a bit each. Sets of elements all below 64 are kept inline in a single
+/// word, as fast as a plain `u64`; larger ones spill to the heap.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet(Words);
+
+/// Always the inline variant when it can hold the set, so that equal sets are equal words.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Words {
+    Inline(u64),
+    /// At least two words, the last one not empty.
+    Spilled(Vec<u64>),
+}
 
 impl BitSet {
     pub const fn new() -> Self {
-        Self(0)
+        Self::from_bits(0)
     }
 
+    /// The set of the positions of the ones in `bits`.
     pub const fn from_bits(bits: u64) -> Self {
-        Self(bits)
+        Self(Words::Inline(bits))
+    }
+
+    /// The set of the positions of the ones in `words`, the first word holding 0 to 63.
+    pub fn from_words(words: &[u64]) -> Self {
+        let used = words
+            .iter()
+            .rposition(|&w| w != 0)
+            .map_or(0, |last| last + 1);
+        match &words[..used] {
+            [] => Self::new(),
+            [word] => Self::from_bits(*word),
+            words => Self(Words::Spilled(words.to_vec())),
+        }
+    }
+
+    /// The words of the set, the first one holding 0 to 63 and the last one never empty unless it
+    /// is the only one.
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        match &self.0 {
+            Words::Inline(word) => slice::from_ref(word),
+            Words::Spilled(words) => words,
+        }
     }
 
     pub fn insert(&mut self, k: usize) {
-        self.0 |= 0b1 << k;
+        match &mut self.0 {
+            Words::Inline(word) if k < WORD => *word |= 0b1 << k,
+            Words::Inline(word) => {
+                let mut words = vec![0; k / WORD + 1];
+                words[0] = *word;
+                words[k / WORD] = 0b1 << (k % WORD);
+                self.0 = Words::Spilled(words);
+            }
+            Words::Spilled(words) => {
+                if words.len() <= k / WORD {
+                    words.resize(k / WORD + 1, 0);
+                }
+                words[k / WORD] |= 0b1 << (k % WORD);
+            }
+        }
     }
 
+    pub fn remove(&mut self, k: usize) {
+        match &mut self.0 {
+            Words::Inline(word) if k < WORD => *word &= !(0b1 << k),
+            Words::Inline(_) => (),
+            Words::Spilled(words) => {
+                if let Some(word) = words.get_mut(k / WORD) {
+                    *word &= !(0b1 << (k % WORD));
+                    if words.last() == Some(&0) {
+                        *self = Self::from_words(words);
+                    }
+                }
+            }
+        }
+    }
+
+    #[must_use]
     pub fn contains(&self, k: usize) -> bool {
-        self.0 & (0b1 << k) != 0
+        match &self.0 {
+            Words::Inline(word) => k < WORD && word & (0b1 << k) != 0,
+            Words::Spilled(words) => words
+                .get(k / WORD)
+                .is_some_and(|word| word & (0b1 << (k % WORD)) != 0),
+        }
     }
 
+    #[must_use]
     pub fn len(&self) -> usize {
-        self.0.count_ones() as usize
+        self.words().iter().map(|w| w.count_ones() as usize).sum()
     }
 
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.0 == Words::Inline(0)
     }
 
+    /// The set of `f` applied to the words of both sets, missing words being empty.
+    fn zip_words(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        if let (Words::Inline(a), Words::Inline(b)) = (&self.0, &other.0) {
+            return Self::from_bits(f(*a, *b));
+        }
+
+        let (a, b) = (self.words(), other.words());
+        let words: Vec<u64> = (0..a.len().max(b.len()))
+            .map(|i| {
+                f(
+                    a.get(i).copied().unwrap_or(0),
+                    b.get(i).copied().unwrap_or(0),
+                )
+            })
+            .collect();
+        Self::from_words(&words)
+    }
+
+    #[must_use]
     pub fn union(&self, other: &Self) -> Self {
-        Self(self.0 | other.0)
+        self.zip_words(other, |a, b| a | b)
     }
 
+    #[must_use]
     pub fn intersection(&self, other: &Self) -> Self {
-        Self(self.0 & other.0)
+        self.zip_words(other, |a, b| a & b)
     }
 
+    #[must_use]
     pub fn is_superset(&self, other: &Self) -> bool {
-        self.0 & other.0 == other.0
+        let words = self.words();
+        other
+            .words()
+            .iter()
+            .enumerate()
+            .all(|(i, o)| words.get(i).copied().unwrap_or(0) & o == *o)
     }
 }
 
@@ -146,3 +418,353 @@ impl Default for BitSet {
         Self::new()
     }
 }
+
+impl Ord for BitSet {
+    /// Ordered like the numbers with the same bits set.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.words(), other.words());
+        a.len()
+            .cmp(&b.len())
+            .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+}
+
+impl PartialOrd for BitSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Snapshot for BitSet {
+    fn save(&self, fields: &mut Vec<i64>) {
+        let words = self.words();
+        fields.push(words.len() as i64);
+        fields.extend(words.iter().map(|&w| w as i64));
+    }
+
+    fn restore(fields: &mut dyn Iterator<Item = i64>) -> Option<Self> {
+        let len = fields.next()?;
+        let words = (0..len)
+            .map(|_| fields.next().map(|w| w as u64))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::from_words(&words))
+    }
+}
+
+/// A rectangle of cells stored row by row, indexed by `Coord` with `x` the column and `y` the row
+/// from the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid of `cell`, which must be at least one cell wide.
+    pub fn new(width: usize, height: usize, cell: T) -> Self
+    where
+        T: Clone,
+    {
+        assert!(width > 0, "grid without columns");
+        Self {
+            width,
+            cells: vec![cell; width * height],
+        }
+    }
+
+    /// The grid of equally long rows, `None` if they are not or there are none.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first()?.len();
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Self {
+            width,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses a character map line by line, every character into a cell by `cell`.
+    pub fn parse(
+        input: &str,
+        mut cell: impl FnMut(Coord, char) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError> {
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(y, l)| {
+                l.chars()
+                    .enumerate()
+                    .map(|(x, c)| cell(Coord::new(x as i64, y as i64), c))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Self::from_rows(rows).ok_or_else(|| ParseError::new("map: not a rectangle"))
+    }
+
+    pub fn width(&self) -> i64 {
+        self.width as i64
+    }
+
+    pub fn height(&self) -> i64 {
+        (self.cells.len() / self.width) as i64
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        (0..self.width()).contains(&coord.x()) && (0..self.height()).contains(&coord.y())
+    }
+
+    fn index(&self, coord: Coord) -> Option<usize> {
+        self.contains(coord)
+            .then(|| coord.y() as usize * self.width + coord.x() as usize)
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.index(coord).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.index(coord).map(|i| &mut self.cells[i])
+    }
+
+    /// Every coordinate of the grid, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width();
+        (0..self.height()).flat_map(move |y| (0..width).map(move |x| Coord::new(x, y)))
+    }
+
+    /// Every cell of the grid with its coordinate, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(&self.cells)
+    }
+
+    /// The up to 4 orthogonal neighbors of `coord` inside the grid.
+    pub fn neighbors4(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        coord.axis_neighbors().filter(|&n| self.contains(n))
+    }
+
+    /// The up to 8 orthogonal and diagonal neighbors of `coord` inside the grid.
+    pub fn neighbors8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        coord.all_neighbors().filter(|&n| self.contains(n))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Row `y`, which must be inside the grid.
+    pub fn row(&self, y: i64) -> &[T] {
+        assert!(
+            (0..self.height()).contains(&y),
+            "row {} outside the grid",
+            y
+        );
+        let start = y as usize * self.width;
+        &self.cells[start..start + self.width]
+    }
+
+    /// Column `x` from top to bottom, which must be inside the grid.
+    pub fn column(&self, x: i64) -> impl Iterator<Item = &T> {
+        assert!(
+            (0..self.width()).contains(&x),
+            "column {} outside the grid",
+            x
+        );
+        self.cells.iter().skip(x as usize).step_by(self.width)
+    }
+
+    /// The cells from `from` on, one `step` after the other until leaving the grid.
+    pub fn ray(&self, from: Coord, step: Coord) -> impl Iterator<Item = (Coord, &T)> {
+        successors(Some(from), move |&c| Some(c + step)).map_while(|c| Some((c, self.get(c)?)))
+    }
+
+    /// A grid of the same shape with every cell mapped by `f`.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        self.get(coord)
+            .unwrap_or_else(|| panic!("{:?} outside the grid", coord))
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut T {
+        self.get_mut(coord)
+            .unwrap_or_else(|| panic!("{:?} outside the grid", coord))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    /// Every row on its own line, cells back to back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A small seeded generator (xorshift64*), enough to build reproducible inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must never be zero
+        Self(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in `0..n`, `n` must be positive.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Uniform in `lo..=hi`.
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo) as u64 + 1) as i64
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_computed() {
+        let (a, b) = (Point::<3>::new(1, -2, 3), Point::<3>::new(-1, 0, 7));
+        assert_eq!(a + b, Point([0, -2, 10]));
+        assert_eq!(a - b, -(b - a));
+        assert_eq!(a * 3, Point([3, -6, 9]));
+        assert_eq!(a.manhattan_distance(b), 8);
+        assert_eq!(a.chebyshev_distance(b), 4);
+        assert_eq!((a.x(), a.y(), a.z()), (1, -2, 3));
+
+        let mut c = Coord::new(4, 5);
+        c.0[1] = 0;
+        assert_eq!(c, RIGHT * 4);
+        assert_eq!(
+            Coord::axis_steps().collect::<Vec<_>>(),
+            [LEFT, RIGHT, UP, DOWN]
+        );
+        assert_eq!(Coord::all_steps().count(), 8);
+        assert_eq!(Point::<3>::all_steps().count(), 26);
+        assert!(Point::<3>::all_neighbors(a).all(|n| n.chebyshev_distance(a) == 1));
+
+        assert_eq!("2,-3,5".parse(), Ok(Point([2, -3, 5])));
+        assert!("2,-3".parse::<Point<3>>().is_err());
+        assert!("2,-3,5,1".parse::<Point<3>>().is_err());
+
+        let bounds = Bounds::of([Coord::new(1, 5), Coord::new(3, -2), Coord::new(2, 0)]).unwrap();
+        assert_eq!(
+            (bounds.min, bounds.max),
+            (Coord::new(1, -2), Coord::new(3, 5))
+        );
+        assert_eq!(bounds.size(), Coord::new(3, 8));
+        assert!(bounds.contains(Coord::new(2, 2)));
+        assert!(!bounds.contains(Coord::new(0, 2)));
+        assert!(bounds.grown(1).contains(Coord::new(0, 6)));
+        assert_eq!(Bounds::<2>::of([]), None);
+    }
+
+    #[test]
+    fn bit_sets_grown() {
+        let mut small = BitSet::new();
+        small.insert(3);
+        small.insert(63);
+        assert_eq!(small, BitSet::from_bits(1 << 63 | 1 << 3));
+        assert!(!small.contains(64) && !small.contains(1000));
+
+        let mut large = small.clone();
+        large.insert(64);
+        large.insert(200);
+        assert!(large.contains(64) && large.contains(200) && !large.contains(199));
+        assert_eq!(large.len(), 4);
+        assert_eq!(large.words().len(), 4);
+        assert!(large.is_superset(&small) && !small.is_superset(&large));
+        assert_eq!(large.intersection(&small), small);
+        assert_eq!(small.union(&large), large);
+        assert!(small < large && BitSet::from_bits(1 << 63) > BitSet::from_bits(1 << 62));
+
+        // removed back to the inline set, equal and hashed the same as the one never grown
+        large.remove(200);
+        large.remove(64);
+        large.remove(500);
+        assert_eq!(large, small);
+        assert_eq!(large.words(), [1 << 63 | 1 << 3]);
+        assert_eq!(FastSet::from_iter([large.clone(), small.clone()]).len(), 1);
+        small.remove(3);
+        small.remove(63);
+        assert!(small.is_empty());
+
+        large.insert(130);
+        let mut fields = vec![];
+        large.save(&mut fields);
+        assert_eq!(BitSet::restore(&mut fields.into_iter()), Some(large));
+    }
+
+    #[test]
+    fn grid_walked() {
+        let grid = Grid::parse("123\n456", |_, c| {
+            c.to_digit(10).ok_or_else(|| ParseError::new("digit"))
+        })
+        .unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Coord::new(2, 1)], 6);
+        assert_eq!(grid.get(Coord::new(3, 0)), None);
+        assert_eq!(grid.get(Coord::new(0, -1)), None);
+
+        let at = |coords: Vec<Coord>| -> Vec<u32> { coords.iter().map(|&c| grid[c]).collect() };
+        assert_eq!(at(grid.neighbors4(Coord::new(0, 0)).collect()), [2, 4]);
+        assert_eq!(
+            at(grid.neighbors8(Coord::new(1, 0)).collect()),
+            [1, 3, 4, 5, 6]
+        );
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.column(1).collect::<Vec<_>>(), [&2, &5]);
+        let ray: Vec<_> = grid.ray(Coord::new(0, 0), Coord::new(1, 1)).collect();
+        assert_eq!(ray, [(Coord::new(0, 0), &1), (Coord::new(1, 1), &5)]);
+        assert_eq!(grid.map(|d| d * 2).to_string(), "246\n81012\n");
+
+        assert!(Grid::parse("12\n3", |_, _| Ok(())).is_err());
+        assert!(Grid::parse("1x", |_, c| {
+            c.to_digit(10).ok_or_else(|| ParseError::new("digit"))
+        })
+        .is_err());
+        assert_eq!(Grid::<u8>::from_rows(vec![]), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_without_columns() {
+        Grid::new(0, 3, 0u8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn row_outside_the_grid() {
+        Grid::new(2, 2, 0u8).row(2);
+    }
+}
@@ -0,0 +1,239 @@
+//! Puzzle inputs encrypted with a key derived from a local passphrase, so they can be committed
+//! without publishing them. Every file is sealed with AES-256-GCM under its own salt and nonce,
+//! with its name as associated data: a file edited, truncated or renamed fails to open.
+
+use std::{
+    env,
+    error::Error,
+    fs, io,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// Directory of the encrypted inputs, committed unlike the plain ones.
+pub const VAULT: &str = "./vault";
+/// File holding the passphrase, overridden by [`PASSPHRASE_VAR`].
+pub const PASSPHRASE_FILE: &str = "./VAULT_PASSPHRASE";
+pub const PASSPHRASE_VAR: &str = "ADVENT_VAULT_PASSPHRASE";
+
+const MAGIC: &[u8] = b"advent-vault 1\n";
+const SALT_LEN: usize = 16;
+const ITERATIONS: u32 = 100_000;
+
+/// The configured passphrase, if any.
+pub fn passphrase() -> Option<String> {
+    env::var(PASSPHRASE_VAR)
+        .ok()
+        .or_else(|| fs::read_to_string(PASSPHRASE_FILE).ok())
+        .map(|p| p.trim_end_matches(['\r', '\n']).to_owned())
+        .filter(|p| !p.is_empty())
+}
+
+pub struct Vault {
+    dir: PathBuf,
+    passphrase: String,
+    rng: SystemRandom,
+}
+
+impl Vault {
+    pub fn new(dir: impl Into<PathBuf>, passphrase: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            passphrase: passphrase.into(),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    fn key(&self, salt: &[u8]) -> LessSafeKey {
+        let mut key = [0; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(ITERATIONS).unwrap(),
+            salt,
+            self.passphrase.as_bytes(),
+            &mut key,
+        );
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).unwrap())
+    }
+
+    /// `text` encrypted for the file `name` of the vault.
+    pub fn seal(&self, name: &str, text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut salt = [0; SALT_LEN];
+        let mut nonce = [0; NONCE_LEN];
+        self.rng
+            .fill(&mut salt)
+            .and_then(|_| self.rng.fill(&mut nonce))
+            .map_err(|_| "no randomness available")?;
+
+        let mut sealed = text.as_bytes().to_vec();
+        self.key(&salt)
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(name),
+                &mut sealed,
+            )
+            .map_err(|_| format!("{} not encrypted", name))?;
+
+        Ok([MAGIC, &salt, &nonce, &sealed].concat())
+    }
+
+    /// The text of the file `name` of the vault from its encrypted `bytes`.
+    pub fn open(&self, name: &str, bytes: &[u8]) -> Result<String, Box<dyn Error>> {
+        let rejected = || {
+            format!(
+                "{} in the vault was tampered with or the passphrase is wrong",
+                name
+            )
+        };
+        let rest = bytes.strip_prefix(MAGIC).ok_or_else(rejected)?;
+        if rest.len() < SALT_LEN + NONCE_LEN {
+            return Err(rejected().into());
+        }
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, sealed) = rest.split_at(NONCE_LEN);
+
+        let mut sealed = sealed.to_vec();
+        let text = self
+            .key(salt)
+            .open_in_place(
+                Nonce::try_assume_unique_for_key(nonce).map_err(|_| rejected())?,
+                Aad::from(name),
+                &mut sealed,
+            )
+            .map_err(|_| rejected())?;
+
+        Ok(String::from_utf8(text.to_vec())?)
+    }
+
+    /// The decrypted file `name`, or `None` if the vault doesn't have it.
+    pub fn read(&self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        match fs::read(self.dir.join(name)) {
+            Ok(bytes) => self.open(name, &bytes).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Encrypts every input of `inputs` into the vault, returning the names of those added or
+    /// changed. Inputs already in the vault are left untouched to keep the history quiet.
+    pub fn lock(&self, inputs: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+
+        let mut locked = vec![];
+        for name in input_names(inputs)? {
+            let text = fs::read_to_string(inputs.join(&name))?;
+            if self.read(&name)?.as_deref() != Some(&text) {
+                fs::write(self.dir.join(&name), self.seal(&name, &text)?)?;
+                locked.push(name);
+            }
+        }
+        Ok(locked)
+    }
+
+    /// Decrypts every input of the vault into `inputs`, returning the names of those written.
+    /// Nothing is written unless the whole vault opens.
+    pub fn unlock(&self, inputs: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut opened = vec![];
+        for name in input_names(&self.dir)? {
+            let text = self.read(&name)?.unwrap_or_default();
+            opened.push((name, text));
+        }
+
+        fs::create_dir_all(inputs)?;
+        for (name, text) in &opened {
+            fs::write(inputs.join(name), text)?;
+        }
+        Ok(opened.into_iter().map(|(name, _)| name).collect())
+    }
+}
+
+/// Names of the day inputs in `dir`, sorted by day.
+fn input_names(dir: &Path) -> io::Result<Vec<String>> {
+    let mut days = vec![];
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(day) = name
+            .to_str()
+            .and_then(|n| n.strip_prefix("day_"))
+            .and_then(|d| d.parse::<usize>().ok())
+        {
+            days.push(day);
+        }
+    }
+    days.sort_unstable();
+    Ok(days.into_iter().map(|d| format!("day_{}", d)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("advent-vault-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn tampering_detected() {
+        let vault = Vault::new(temp("unused"), "correct horse");
+        let sealed = vault.seal("day_1", "1000\n2000").unwrap();
+        assert!(!sealed.windows(4).any(|w| w == b"1000"));
+        assert_eq!(vault.open("day_1", &sealed).unwrap(), "1000\n2000");
+        // sealing twice never gives the same bytes
+        assert_ne!(vault.seal("day_1", "1000\n2000").unwrap(), sealed);
+
+        let mut flipped = sealed.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(vault.open("day_1", &flipped).is_err());
+        assert!(vault.open("day_1", &sealed[..sealed.len() - 1]).is_err());
+        assert!(vault.open("day_1", &sealed[..MAGIC.len() + 3]).is_err());
+        assert!(vault.open("day_2", &sealed).is_err());
+        assert!(Vault::new(temp("unused"), "battery staple")
+            .open("day_1", &sealed)
+            .is_err());
+    }
+
+    #[test]
+    fn locked_and_unlocked() {
+        let (inputs, dir, restored) = (temp("inputs"), temp("vault"), temp("restored"));
+        fs::create_dir_all(&inputs).unwrap();
+        fs::write(inputs.join("day_2"), "A Y").unwrap();
+        fs::write(inputs.join("day_10"), "noop").unwrap();
+        fs::write(inputs.join("leaderboard_1"), "{}").unwrap();
+
+        let vault = Vault::new(&dir, "correct horse");
+        assert_eq!(vault.lock(&inputs).unwrap(), ["day_2", "day_10"]);
+        assert!(!dir.join("leaderboard_1").exists());
+        let sealed = fs::read(dir.join("day_2")).unwrap();
+
+        fs::write(inputs.join("day_10"), "addx 1").unwrap();
+        assert_eq!(vault.lock(&inputs).unwrap(), ["day_10"]);
+        assert_eq!(fs::read(dir.join("day_2")).unwrap(), sealed);
+
+        assert_eq!(vault.read("day_2").unwrap().as_deref(), Some("A Y"));
+        assert_eq!(vault.read("day_3").unwrap(), None);
+        assert_eq!(vault.unlock(&restored).unwrap(), ["day_2", "day_10"]);
+        assert_eq!(
+            fs::read_to_string(restored.join("day_10")).unwrap(),
+            "addx 1"
+        );
+
+        // a wrong passphrase writes nothing
+        fs::remove_dir_all(&restored).unwrap();
+        assert!(Vault::new(&dir, "battery staple")
+            .unlock(&restored)
+            .is_err());
+        assert!(!restored.exists());
+
+        for dir in [inputs, dir, restored] {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Runs every variant of every day on each of its fixtures and checks the answers.
+
+use std::path::Path;
+
+use advent_2022::{fixtures, registry::DAYS};
+
+fn root() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))
+}
+
+#[test]
+fn every_day_has_a_fixture() {
+    for day in DAYS {
+        let fixtures = fixtures::load(root(), day.day).unwrap();
+        assert!(!fixtures.is_empty(), "no fixture for day {}", day.day);
+    }
+}
+
+#[test]
+fn fixtures_answered() {
+    let mut wrong = vec![];
+
+    for day in DAYS {
+        for fixture in fixtures::load(root(), day.day).unwrap() {
+            let params = fixture.params(day).unwrap();
+            for (variant, solution) in day.all_variants() {
+                let answer = solution(&fixture.input, &params).unwrap();
+                if !fixture.expects(&answer) {
+                    wrong.push((day.day, fixture.name.clone(), variant, answer));
+                }
+            }
+        }
+    }
+
+    assert!(wrong.is_empty(), "wrong answers: {:#?}", wrong);
+}
@@ -0,0 +1,75 @@
+//! Behavior of the HTTP client against a local stub of the puzzle site.
+
+mod stub;
+
+use std::time::{Duration, Instant};
+
+use advent_2022::client::Client;
+use stub::Stub;
+
+fn client(stub: &Stub) -> Client {
+    Client::new(&stub.url, "0123abcd\n", Some("someone@example.com\n"))
+        .with_retries(2, Duration::from_millis(10))
+        .with_throttle(Duration::ZERO, None)
+}
+
+#[test]
+fn identifies_itself() {
+    let stub = Stub::serve(vec![(200, "1000\n2000\n".to_owned())]);
+    let answer = client(&stub).get("/day/1/input").unwrap();
+    assert_eq!(answer, "1000\n2000\n");
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/day/1/input");
+    assert_eq!(requests[0].header("Cookie"), Some("session=0123abcd"));
+    assert_eq!(
+        requests[0].header("User-Agent"),
+        Some("github.com/ivfranco/advent-of-code-2022 by someone@example.com")
+    );
+}
+
+#[test]
+fn retries_server_errors() {
+    let stub = Stub::serve(vec![
+        (502, "Bad Gateway".to_owned()),
+        (503, "Service Unavailable".to_owned()),
+        (200, "input".to_owned()),
+    ]);
+    assert_eq!(client(&stub).get("/day/2/input").unwrap(), "input");
+    assert_eq!(stub.requests().len(), 3);
+}
+
+#[test]
+fn retries_are_bounded() {
+    let stub = Stub::serve(vec![(500, "Internal Server Error".to_owned()); 3]);
+    assert!(client(&stub).get("/day/3/input").is_err());
+    assert_eq!(stub.requests().len(), 3);
+}
+
+#[test]
+fn client_errors_not_retried() {
+    let stub = Stub::serve(vec![(400, "Bad Request".to_owned())]);
+    assert!(client(&stub).get("/day/4/input").is_err());
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn requests_throttled() {
+    let interval = Duration::from_millis(200);
+    let stamp = std::env::temp_dir().join(format!("advent-stamp-{}", std::process::id()));
+    let stub = Stub::serve(vec![(200, "input".to_owned()); 3]);
+
+    let start = Instant::now();
+    let mut client = client(&stub).with_throttle(interval, Some(stamp.clone()));
+    client.get("/day/5/input").unwrap();
+    client.get("/day/6/input").unwrap();
+    assert!(start.elapsed() >= interval);
+
+    // a fresh client, like the next run of the binary, still waits for the stamped request
+    let mut client = Client::new(&stub.url, "", None).with_throttle(interval, Some(stamp.clone()));
+    client.get("/day/7/input").unwrap();
+    assert!(start.elapsed() >= interval * 2);
+
+    std::fs::remove_file(stamp).unwrap();
+}
@@ -0,0 +1,65 @@
+//! Fetching and caching a private leaderboard from a local stub serving a recorded response.
+
+mod stub;
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use advent_2022::{client::Client, leaderboard};
+use stub::Stub;
+
+const RECORDED: &str = include_str!("data/leaderboard.json");
+
+fn client(stub: &Stub) -> Client {
+    Client::new(&stub.url, "0123abcd", None)
+        .with_retries(0, Duration::ZERO)
+        .with_throttle(Duration::ZERO, None)
+}
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("advent-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn fetched_once_then_cached() {
+    let stub = Stub::serve(vec![(200, RECORDED.to_owned())]);
+    let dir = cache_dir("leaderboard-cached");
+
+    let fetched = leaderboard::load_or_fetch(&mut client(&stub), &dir, "123456").unwrap();
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/leaderboard/private/view/123456.json");
+    assert_eq!(requests[0].header("Cookie"), Some("session=0123abcd"));
+
+    // the stub is gone after one response, the second load can only come from the cache
+    let cached = leaderboard::load_or_fetch(&mut client(&stub), &dir, "123456").unwrap();
+    assert_eq!(fetched, cached);
+
+    let names: Vec<String> = cached.members.iter().map(|m| m.display_name()).collect();
+    assert_eq!(names, ["ivfranco", "(anonymous user #234567)", "lurker"]);
+    let rendered = leaderboard::render(&cached);
+    assert!(rendered.contains("  1     52      5  **+......................  ivfranco"));
+    assert!(rendered.contains("Day 3"));
+    assert!(!rendered.contains("Day 4"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn html_not_cached() {
+    let stub = Stub::serve(vec![(200, "<!DOCTYPE html>\n<html></html>".to_owned())]);
+    let dir = cache_dir("leaderboard-html");
+
+    assert!(leaderboard::load_or_fetch(&mut client(&stub), &dir, "123456").is_err());
+    assert!(!dir.join("leaderboard_123456.json").exists());
+}
+
+#[test]
+fn invalid_id_not_requested() {
+    let stub = Stub::serve(vec![]);
+    let dir = cache_dir("leaderboard-invalid");
+
+    assert!(leaderboard::load_or_fetch(&mut client(&stub), &dir, "../day_1").is_err());
+    assert!(stub.requests().is_empty());
+}
@@ -0,0 +1,60 @@
+//! Prints every day's model back as puzzle text and checks nothing was lost on the way.
+
+use std::path::Path;
+
+use advent_2022::{
+    fixtures,
+    input::normalize,
+    registry::{Day, Print, DAYS},
+    utils::Rng,
+};
+
+fn root() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))
+}
+
+fn printer(day: &Day) -> Print {
+    day.print
+        .unwrap_or_else(|| panic!("no printer for day {}", day.day))
+}
+
+#[test]
+fn every_day_has_a_printer() {
+    for day in DAYS {
+        printer(day);
+    }
+}
+
+/// Every fixture prints back as itself, and the printer's model parses back the same.
+#[test]
+fn fixtures_printed_back() {
+    let mut wrong = vec![];
+
+    for day in DAYS {
+        let print = printer(day);
+        for fixture in fixtures::load(root(), day.day).unwrap() {
+            let input = normalize(&fixture.input);
+            let printed = normalize(&print(&input).unwrap());
+            if printed != input {
+                wrong.push((day.day, fixture.name.clone(), printed));
+            }
+        }
+    }
+
+    assert!(wrong.is_empty(), "changed by printing: {:#?}", wrong);
+}
+
+#[test]
+fn generated_inputs_printed_back() {
+    for day in DAYS {
+        let (Some(generate), print) = (day.generate, printer(day)) else {
+            continue;
+        };
+        let mut rng = Rng::new(day.day as u64);
+        for size in [1, 10, 100] {
+            let input = normalize(&generate(size, &mut rng));
+            let printed = normalize(&print(&input).unwrap());
+            assert_eq!(printed, input, "day {} of size {}", day.day, size);
+        }
+    }
+}
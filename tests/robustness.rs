@@ -0,0 +1,168 @@
+//! Feeds every day's parser random mutations of its example input, and its solution those the
+//! parser accepts. A mutated input may well be rejected, but only through a returned error, never a
+//! panic.
+
+use std::{panic, path::Path, sync::mpsc, thread, time::Duration};
+
+use advent_2022::{
+    fixtures,
+    params::Params,
+    registry::{Day, DAYS},
+    utils::Rng,
+};
+
+const MUTATIONS_PER_DAY: usize = 2000;
+
+/// The first mutated inputs the parser accepts are solved too, fewer of them as solving takes much
+/// longer.
+const SOLVED_PER_DAY: usize = 10;
+/// A solution taking longer than this on a mutated input fails the test like a panic would.
+const SOLVE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Fragments that tend to hit edge cases: overflowing or negative numbers, empty sections and
+/// stray separators.
+const FRAGMENTS: &[&str] = &[
+    "0",
+    "-1",
+    "99999999999999999999999",
+    "\n",
+    "\n\n",
+    " ",
+    ",",
+    "[",
+    "]",
+    "=",
+    "\r\n",
+];
+
+/// Uniform in `0..n`.
+fn below(rng: &mut Rng, n: usize) -> usize {
+    rng.below(n as u64) as usize
+}
+
+/// A range of at most 16 elements inside `0..len`.
+fn span(rng: &mut Rng, len: usize) -> (usize, usize) {
+    let start = below(rng, len + 1);
+    let end = start + below(rng, len - start + 1).min(16);
+    (start, end)
+}
+
+fn mutate(rng: &mut Rng, example: &[char]) -> String {
+    let mut chars = example.to_vec();
+
+    for _ in 0..=below(rng, 3) {
+        let (start, end) = span(rng, chars.len());
+        match below(rng, 6) {
+            0 => chars.truncate(start),
+            1 => {
+                chars.drain(start..end);
+            }
+            2 => {
+                let duplicate: Vec<char> = chars[start..end].to_vec();
+                chars.splice(start..start, duplicate);
+            }
+            3 => {
+                let c = rng.pick(example);
+                chars.insert(start, c);
+            }
+            4 if start < chars.len() => {
+                chars[start] = rng.pick(example);
+            }
+            _ => {
+                let fragment = rng.pick(FRAGMENTS);
+                chars.splice(start..start, fragment.chars());
+            }
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Every fixture of every day with the parameters it is solved with.
+fn examples() -> Vec<(&'static Day, Params, String)> {
+    let root = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"));
+    DAYS.iter()
+        .flat_map(|day| {
+            fixtures::load(root, day.day)
+                .unwrap()
+                .into_iter()
+                .map(move |fixture| (day, fixture.params(day).unwrap(), fixture.input))
+        })
+        .collect()
+}
+
+#[test]
+fn examples_are_valid() {
+    for (day, _, example) in examples() {
+        assert_eq!(
+            (day.validate)(&example),
+            Ok(()),
+            "example of day {}",
+            day.day
+        );
+    }
+}
+
+#[test]
+fn parsers_never_panic() {
+    let mut panicked = vec![];
+
+    for (day, _, example) in examples() {
+        let example: Vec<char> = example.chars().collect();
+        let mut rng = Rng::new(day.day as u64);
+
+        for _ in 0..MUTATIONS_PER_DAY {
+            let mutated = mutate(&mut rng, &example);
+            if panic::catch_unwind(|| (day.validate)(&mutated)).is_err() {
+                panicked.push((day.day, mutated));
+                break;
+            }
+        }
+    }
+
+    assert!(panicked.is_empty(), "parsers panicked on: {:#?}", panicked);
+}
+
+#[test]
+fn solutions_never_panic() {
+    let mut failed = vec![];
+
+    'days: for (day, params, example) in examples() {
+        let example: Vec<char> = example.chars().collect();
+        let mut rng = Rng::new(day.day as u64);
+        let mut solved = 0;
+
+        for _ in 0..MUTATIONS_PER_DAY {
+            if solved == SOLVED_PER_DAY {
+                break;
+            }
+            let mutated = mutate(&mut rng, &example);
+            if (day.validate)(&mutated).is_err() {
+                continue;
+            }
+            solved += 1;
+
+            // solved on its own thread to tell a hanging solution from a slow one
+            let (sender, receiver) = mpsc::channel();
+            let (solution, input, params) = (day.solution, mutated.clone(), params.clone());
+            thread::spawn(move || {
+                let _ = sender.send(panic::catch_unwind(|| solution(&input, &params)).is_ok());
+            });
+            match receiver.recv_timeout(SOLVE_TIMEOUT) {
+                Ok(true) => (),
+                Ok(false) => {
+                    failed.push((day.day, "panicked", mutated));
+                    break;
+                }
+                Err(_) => {
+                    // the solution keeps running, so the test fails right away instead of going
+                    // on beside it
+                    failed.push((day.day, "timed out", mutated));
+                    break 'days;
+                }
+            }
+        }
+    }
+
+    assert!(failed.is_empty(), "solutions failed on: {:#?}", failed);
+}
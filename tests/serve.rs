@@ -0,0 +1,180 @@
+//! The solve service, spoken to over raw TCP to also cover malformed and oversized requests.
+
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use advent_2022::{
+    json::{self, Value},
+    serve::{self, Limits},
+};
+
+fn start(limits: Limits) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve::serve(listener, limits));
+    addr
+}
+
+fn limits() -> Limits {
+    Limits {
+        max_body: 4096,
+        read_timeout: Duration::from_millis(500),
+        ..Limits::default()
+    }
+}
+
+/// Sends `raw` as is and returns the status with the parsed body.
+fn send(addr: SocketAddr, raw: &[u8]) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(raw).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    (status, json::parse(body).unwrap())
+}
+
+fn post(addr: SocketAddr, path: &str, body: &str) -> (u16, Value) {
+    let raw = format!(
+        "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+        path,
+        body.len(),
+        body
+    );
+    send(addr, raw.as_bytes())
+}
+
+fn error(value: &Value) -> &str {
+    value.get("error").and_then(Value::as_str).unwrap()
+}
+
+const DAY_1: &str = "1000\r\n2000\r\n\r\n3000\r\n";
+
+#[test]
+fn solves_with_parts_and_timings() {
+    let addr = start(limits());
+
+    let (status, body) = post(addr, "/solve/1", DAY_1);
+    assert_eq!(status, 200);
+    assert_eq!(body.get("answer").unwrap().as_str(), Some("3000, 6000"));
+    let parts = body.get("parts").unwrap().as_array().unwrap();
+    assert_eq!(parts, [Value::from("3000"), Value::from("6000")]);
+    let timings = body.get("timings").unwrap();
+    assert!(timings.get("parse_ms").unwrap().as_f64().unwrap() >= 0.0);
+    assert!(timings.get("solve_ms").unwrap().as_f64().unwrap() >= 0.0);
+
+    let example = include_str!("../fixtures/day_7/example.in");
+    let (status, body) = post(addr, "/solve/7", example);
+    assert_eq!(status, 200);
+    let timings = body.get("timings").unwrap();
+    let solve = timings.get("solve_ms").unwrap().as_f64().unwrap();
+    let part_two = timings.get("part_two_ms").unwrap().as_f64().unwrap();
+    assert!(part_two <= solve);
+
+    let (status, body) = post(addr, "/solve/1?variant=sorting", DAY_1);
+    assert_eq!(status, 200);
+    assert_eq!(body.get("variant").unwrap().as_str(), Some("sorting"));
+}
+
+#[test]
+fn parameters_from_query() {
+    let addr = start(limits());
+    let example = include_str!("../fixtures/day_15/example.in");
+
+    let (status, body) = post(addr, "/solve/15?row=10&bound=20", example);
+    assert_eq!(status, 200);
+    assert_eq!(body.get("answer").unwrap().as_str(), Some("26, 56000011"));
+
+    let (status, body) = post(addr, "/solve/15?column=10", example);
+    assert_eq!(status, 400);
+    assert!(error(&body).contains("unknown parameter column"));
+
+    let (status, body) = post(addr, "/solve/15?r%6Fw=%31%30&bound=20", example);
+    assert_eq!(status, 200);
+    assert_eq!(body.get("answer").unwrap().as_str(), Some("26, 56000011"));
+    assert_eq!(post(addr, "/solve/15?row=%1", example).0, 400);
+    assert_eq!(post(addr, "/solve/15?row=%zz", example).0, 400);
+}
+
+#[test]
+fn errors() {
+    let addr = start(limits());
+
+    let (status, body) = post(addr, "/solve/2", "A Q");
+    assert_eq!(status, 422);
+    assert!(error(&body).contains("invalid input"));
+
+    assert_eq!(post(addr, "/solve/26", DAY_1).0, 404);
+    assert_eq!(post(addr, "/solve/one", DAY_1).0, 404);
+    assert_eq!(post(addr, "/solve/1?variant=magic", DAY_1).0, 404);
+    assert_eq!(post(addr, "/unknown", DAY_1).0, 404);
+    assert_eq!(send(addr, b"GET /solve/1 HTTP/1.1\r\n\r\n").0, 405);
+    assert_eq!(send(addr, b"nonsense\r\n\r\n").0, 400);
+
+    let (status, body) = send(addr, b"GET /days HTTP/1.1\r\n\r\n");
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 25);
+}
+
+#[test]
+fn size_limits() {
+    let addr = start(limits());
+
+    assert_eq!(post(addr, "/solve/1", &"1\n".repeat(4096)).0, 413);
+
+    let huge_header = format!(
+        "POST /solve/1 HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+        "a".repeat(16 * 1024)
+    );
+    assert_eq!(send(addr, huge_header.as_bytes()).0, 431);
+
+    let chunked = b"POST /solve/1 HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n";
+    assert_eq!(send(addr, chunked).0, 411);
+}
+
+#[test]
+fn timeouts() {
+    let addr = start(Limits {
+        solve_timeout: Duration::from_millis(1),
+        ..limits()
+    });
+
+    // announced but never sent body
+    let started = "POST /solve/1 HTTP/1.1\r\nContent-Length: 100\r\n\r\n1000";
+    assert_eq!(send_without_closing(addr, started.as_bytes()), 408);
+
+    let example = include_str!("../fixtures/day_16/example.in");
+    let (status, body) = post(addr, "/solve/16", example);
+    assert_eq!(status, 504);
+    assert!(error(&body).contains("no answer within"));
+}
+
+#[test]
+fn timed_out_solutions_hold_their_connection() {
+    let addr = start(Limits {
+        solve_timeout: Duration::from_millis(1),
+        max_connections: 1,
+        ..limits()
+    });
+
+    let example = include_str!("../fixtures/day_16/example.in");
+    assert_eq!(post(addr, "/solve/16", example).0, 504);
+    // the solution of day 16 is still running
+    let (status, body) = post(addr, "/solve/1", DAY_1);
+    assert_eq!(status, 503);
+    assert_eq!(error(&body), "too many requests");
+}
+
+/// Like `send`, but keeps the connection open to play a slow client.
+fn send_without_closing(addr: SocketAddr, raw: &[u8]) -> u16 {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(raw).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response[9..12].parse().unwrap()
+}
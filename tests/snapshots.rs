@@ -0,0 +1,46 @@
+//! Draws every rendering of every day on each of its fixtures and checks them against the
+//! snapshots stored next to the fixtures. Run with `UPDATE_SNAPSHOTS=1` to accept changes.
+
+use std::path::Path;
+
+use advent_2022::{
+    fixtures,
+    registry::DAYS,
+    snapshot::{self, Outcome},
+};
+
+fn root() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))
+}
+
+#[test]
+fn renderings_match_snapshots() {
+    let update = snapshot::updating();
+    let mut failures = vec![];
+    let mut checked = 0;
+
+    for day in DAYS.iter().filter(|d| !d.renderings.is_empty()) {
+        for fixture in fixtures::load(root(), day.day).unwrap() {
+            let params = fixture.params(day).unwrap();
+            for (name, render) in day.renderings {
+                let rendered = render(&fixture.input, &params).unwrap();
+                let path = snapshot::path(root(), day.day, &fixture.name, name);
+                match snapshot::check(&path, &rendered, update).unwrap() {
+                    Outcome::Matched | Outcome::Written => (),
+                    Outcome::Missing => failures.push(format!(
+                        "{}: missing, run with {}=1 to write it",
+                        path.display(),
+                        snapshot::UPDATE_VAR
+                    )),
+                    Outcome::Differs(diff) => {
+                        failures.push(format!("{}: differs\n{}", path.display(), diff))
+                    }
+                }
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    assert!(checked >= 3, "only {} renderings checked", checked);
+}
@@ -0,0 +1,46 @@
+//! Syncing stars from a local stub serving a recorded calendar page.
+
+mod stub;
+
+use std::time::Duration;
+
+use advent_2022::{
+    client::Client,
+    progress::{self, Progress},
+};
+use stub::Stub;
+
+const CALENDAR: &str = include_str!("data/calendar.html");
+
+fn client(stub: &Stub) -> Client {
+    Client::new(&stub.url, "0123abcd", None)
+        .with_retries(0, Duration::ZERO)
+        .with_throttle(Duration::ZERO, None)
+}
+
+#[test]
+fn stars_synced_from_calendar() {
+    let stub = Stub::serve(vec![(200, CALENDAR.to_owned())]);
+    let mut progress = Progress::default();
+    progress.earned(4, 1);
+
+    progress::sync_stars(&mut client(&stub), &mut progress).unwrap();
+    let requests = stub.requests();
+    assert_eq!(requests[0].path, "/");
+    // stars only show on the calendar of a logged in user
+    assert_eq!(requests[0].header("Cookie"), Some("session=0123abcd"));
+
+    let stars: Vec<u8> = (1..=5).map(|day| progress.day(day).stars).collect();
+    // a day missing from the page keeps what was known of it
+    assert_eq!(stars, [2, 1, 0, 1, 0]);
+    assert_eq!(progress.stars(), 4);
+}
+
+#[test]
+fn not_a_calendar_rejected() {
+    let stub = Stub::serve(vec![(200, "<!DOCTYPE html><p>Log in</p>".to_owned())]);
+    let mut progress = Progress::default();
+
+    assert!(progress::sync_stars(&mut client(&stub), &mut progress).is_err());
+    assert_eq!(progress, Progress::default());
+}